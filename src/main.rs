@@ -1,38 +1,423 @@
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseEvent, MouseEventKind,
+    },
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     execute,
     cursor,
 };
 use ratatui::{
     backend::CrosstermBackend,
-    layout::Rect,
+    layout::{Constraint, Layout, Rect},
     text::{Line, Span, Text},
-    widgets::{Paragraph, Clear},
+    widgets::{
+        Block, Borders, Clear, Padding, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState,
+    },
     Frame, Terminal,
-    style::{Color, Style}
+    style::{Color, Modifier, Style}
 };
-use clap::{Arg, Command};
-use std::io::{self, BufRead, BufReader};
+use chrono::{Local, SecondsFormat};
+use clap::{Arg, ArgAction, Command};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Seek, SeekFrom, Write};
 use std::panic;
-use std::process::{Command as ProcessCommand, Stdio};
-use std::time::{Duration, Instant};
-use std::fs::File;
+use std::process::{Child, Command as ProcessCommand, Stdio};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use regex::Regex;
+use flate2::read::GzDecoder;
+use unicode_width::UnicodeWidthChar;
 
-#[derive(Debug)]
+const KEY_BINDINGS: &[(&str, &str)] = &[
+    ("↑/↓ j/k", "垂直滚动"),
+    ("←/→ h/l", "水平滚动"),
+    ("PgUp/PgDn", "垂直翻页"),
+    ("Ctrl+D/U", "向下/向上滚动半页"),
+    ("鼠标滚轮", "垂直滚动 (Shift+滚轮 水平滚动)"),
+    ("Home/End", "水平跳转"),
+    ("Ctrl+Home/End", "垂直跳转 (Ctrl+End 开启跟随)"),
+    ("g/G", "跳到顶部/底部 (G 开启跟随)"),
+    ("F", "开启/关闭跟随模式"),
+    ("空格/p", "暂停/继续"),
+    ("r", "立即刷新"),
+    ("x/Ctrl+K", "终止正在运行的命令"),
+    ("s", "保存快照到当前目录"),
+    ("+/-", "加快/减慢刷新 (间隔除以/乘以 1.5)"),
+    ("#", "显示/隐藏行号"),
+    ("t", "显示/隐藏状态栏"),
+    ("Tab", "切换窗格 (多个 -f/-c 或 --split-stderr 时)"),
+    ("[/]", "缩小/放大标准错误窗格 (--split-stderr)"),
+    ("e", "显示/隐藏标准错误输出"),
+    ("w", "切换自动换行"),
+    ("/", "搜索 (Enter 确认, Esc 取消)"),
+    ("n/N", "下一个/上一个匹配"),
+    (":", "跳转到指定行号 (:$ 跳到末尾)"),
+    ("!", "输入新命令替换当前内容 (↑/↓ 浏览历史)"),
+    ("&", "追加过滤 (正则表达式, !前缀表示排除, 留空清除)"),
+    ("b", "重置差异基准 (--differences=permanent)"),
+    ("?/F1", "显示帮助 (j/k 滚动, 其他键关闭)"),
+    ("q/Ctrl+C", "退出"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DiffMode {
+    Changes,
+    Permanent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OverlapPolicy {
+    Skip,
+    Wait,
+    Kill,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InputMode {
+    Search,
+    Filter,
+    Goto,
+    Command,
+}
+
+#[derive(Debug, Clone)]
+struct LineFilter {
+    regex: Regex,
+    exclude: bool,
+}
+
+impl LineFilter {
+    fn parse(pattern: &str) -> Result<Self, regex::Error> {
+        match pattern.strip_prefix('!') {
+            Some(rest) => Ok(Self { regex: Regex::new(rest)?, exclude: true }),
+            None => Ok(Self { regex: Regex::new(pattern)?, exclude: false }),
+        }
+    }
+
+    fn keeps(&self, line: &str) -> bool {
+        self.regex.is_match(line) != self.exclude
+    }
+
+    fn label(&self) -> String {
+        if self.exclude {
+            format!("!{}", self.regex.as_str())
+        } else {
+            self.regex.as_str().to_string()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SortKey {
+    field: usize,
+    numeric: bool,
+    reverse: bool,
+}
+
+impl SortKey {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        let (a, b) = (strip_ansi(a), strip_ansi(b));
+        let a = a.split_whitespace().nth(self.field);
+        let b = b.split_whitespace().nth(self.field);
+        let ordering = if self.numeric {
+            let parse = |field: Option<&str>| field.and_then(|field| field.parse::<f64>().ok());
+            match (parse(a), parse(b)) {
+                (Some(x), Some(y)) => x.total_cmp(&y),
+                (Some(_), None) => return Ordering::Less,
+                (None, Some(_)) => return Ordering::Greater,
+                (None, None) => a.cmp(&b),
+            }
+        } else {
+            a.cmp(&b)
+        };
+        if self.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(f64),
+    Field(Regex, usize),
+    Binary(Box<Expr>, char, Box<Expr>),
+}
+
+impl Expr {
+    fn parse(text: &str) -> Result<Self, String> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut pos = 0;
+        let expr = Self::parse_sum(&chars, &mut pos)?;
+        skip_spaces(&chars, &mut pos);
+        if pos < chars.len() {
+            return Err(format!("无法解析 '{}'", chars[pos..].iter().collect::<String>()));
+        }
+        Ok(expr)
+    }
+
+    fn parse_sum(chars: &[char], pos: &mut usize) -> Result<Self, String> {
+        let mut expr = Self::parse_product(chars, pos)?;
+        loop {
+            skip_spaces(chars, pos);
+            match chars.get(*pos) {
+                Some(&op @ ('+' | '-')) => {
+                    *pos += 1;
+                    expr = Expr::Binary(Box::new(expr), op, Box::new(Self::parse_product(chars, pos)?));
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    fn parse_product(chars: &[char], pos: &mut usize) -> Result<Self, String> {
+        let mut expr = Self::parse_operand(chars, pos)?;
+        loop {
+            skip_spaces(chars, pos);
+            match chars.get(*pos) {
+                Some(&op @ ('*' | '/')) => {
+                    *pos += 1;
+                    expr = Expr::Binary(Box::new(expr), op, Box::new(Self::parse_operand(chars, pos)?));
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    fn parse_operand(chars: &[char], pos: &mut usize) -> Result<Self, String> {
+        skip_spaces(chars, pos);
+        match chars.get(*pos) {
+            Some('(') => {
+                *pos += 1;
+                let expr = Self::parse_sum(chars, pos)?;
+                skip_spaces(chars, pos);
+                if chars.get(*pos) != Some(&')') {
+                    return Err("缺少 ')'".to_string());
+                }
+                *pos += 1;
+                Ok(expr)
+            }
+            Some('/') => {
+                *pos += 1;
+                let mut pattern = String::new();
+                loop {
+                    match chars.get(*pos) {
+                        Some('\\') if chars.get(*pos + 1) == Some(&'/') => {
+                            pattern.push('/');
+                            *pos += 2;
+                        }
+                        Some('/') => break,
+                        Some(&c) => {
+                            pattern.push(c);
+                            *pos += 1;
+                        }
+                        None => return Err(format!("正则表达式缺少结尾的 '/': /{}", pattern)),
+                    }
+                }
+                *pos += 1;
+                let start = *pos;
+                while chars.get(*pos).is_some_and(char::is_ascii_digit) {
+                    *pos += 1;
+                }
+                let field = chars[start..*pos]
+                    .iter()
+                    .collect::<String>()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&field| field > 0)
+                    .ok_or_else(|| format!("/{}/ 后缺少字段序号 (从 1 开始)", pattern))?;
+                let regex = Regex::new(&pattern).map_err(|e| format!("无效的正则表达式 {}: {}", pattern, e))?;
+                Ok(Expr::Field(regex, field - 1))
+            }
+            Some(c) if c.is_ascii_digit() || *c == '.' => {
+                let start = *pos;
+                while chars.get(*pos).is_some_and(|c| c.is_ascii_digit() || *c == '.') {
+                    *pos += 1;
+                }
+                let number: String = chars[start..*pos].iter().collect();
+                number.parse().map(Expr::Number).map_err(|_| format!("无效的数字: {}", number))
+            }
+            Some(c) => Err(format!("意外的字符 '{}'", c)),
+            None => Err("表达式不完整".to_string()),
+        }
+    }
+
+    fn eval(&self, lines: &[String]) -> Result<f64, String> {
+        match self {
+            Expr::Number(value) => Ok(*value),
+            Expr::Field(regex, field) => {
+                let line = lines
+                    .iter()
+                    .map(|line| strip_ansi(line))
+                    .find(|line| regex.is_match(line))
+                    .ok_or_else(|| format!("没有匹配 /{}/ 的行", regex.as_str()))?;
+                let value = line
+                    .split_whitespace()
+                    .nth(*field)
+                    .ok_or_else(|| format!("/{}/ 没有第 {} 个字段", regex.as_str(), field + 1))?;
+                value.parse().map_err(|_| format!("字段不是数字: {}", value))
+            }
+            Expr::Binary(left, op, right) => {
+                let (left, right) = (left.eval(lines)?, right.eval(lines)?);
+                match op {
+                    '+' => Ok(left + right),
+                    '-' => Ok(left - right),
+                    '*' => Ok(left * right),
+                    _ if right == 0.0 => Err("除数为零".to_string()),
+                    _ => Ok(left / right),
+                }
+            }
+        }
+    }
+}
+
+fn skip_spaces(chars: &[char], pos: &mut usize) {
+    while chars.get(*pos).is_some_and(|c| c.is_whitespace()) {
+        *pos += 1;
+    }
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ColorRule {
+    regex: Regex,
+    color: Color,
+}
+
+impl ColorRule {
+    fn parse(rule: &str) -> Result<Self, String> {
+        let (pattern, color) = rule
+            .rsplit_once('=')
+            .ok_or_else(|| format!("颜色规则缺少 '=': {}", rule))?;
+        let regex = Regex::new(pattern).map_err(|e| format!("无效的正则表达式 {}: {}", pattern, e))?;
+        let color = color
+            .trim()
+            .parse::<Color>()
+            .map_err(|_| format!("未知的颜色: {}", color))?;
+        Ok(Self { regex, color })
+    }
+}
+
+enum Source {
+    File(String),
+    Url(String),
+    Command(String, Vec<String>),
+}
+
+#[derive(Debug, Clone)]
 struct AppConfig {
     interval: Duration,
+    speed: f64,
     file: Option<String>,
-    command: Option<(String, Vec<String>)>,
+    command: Option<String>,
+    url: Option<String>,
+    headers: Vec<(String, String)>,
+    argv: Vec<String>,
+    chdir: Option<String>,
+    env: Vec<(String, String)>,
+    clean_env: bool,
+    pty: bool,
+    pty_size: (u16, u16),
+    tabsize: usize,
+    head: Option<usize>,
+    tail: Option<usize>,
+    no_color: bool,
+    no_stderr: bool,
+    split_stderr: bool,
+    fields: Vec<usize>,
+    delimiter: Option<String>,
+    filters: Vec<LineFilter>,
+    sort: Option<SortKey>,
+    color_rules: Vec<ColorRule>,
+    highlight: Option<Regex>,
+    compute: Option<Expr>,
+    status_color: Color,
+    status_format: Option<String>,
+    clock: bool,
+    show_status: bool,
+    follow: bool,
+    line_numbers: bool,
+    wrap: bool,
+    header: usize,
+    freeze_cols: usize,
+    mouse: bool,
+    history: bool,
+    scrollbar: bool,
+    stdin: Option<Arc<Mutex<Vec<String>>>>,
+    running: Arc<Mutex<Option<u32>>>,
+    file_cache: Arc<Mutex<Option<FileCache>>>,
+    max_bytes: u64,
+    decompressed: Arc<Mutex<Option<(Compression, u64)>>>,
+    differences: Option<DiffMode>,
+    changes_only: bool,
+    rate: bool,
+    once: bool,
+    log: Option<Arc<Mutex<io::BufWriter<File>>>>,
+    snapshot_ansi: bool,
+    chgexit: bool,
+    timeout: Option<Duration>,
+    errexit: bool,
+    keep_on_error: bool,
+    equexit: Option<u64>,
+    times: Option<u64>,
+    precise: bool,
+    overlap: OverlapPolicy,
+    exit_on_match: Option<Regex>,
+    bell: bool,
+    beep_on_match: Option<Regex>,
+    confirm_quit: bool,
 }
 
-struct App {
+type ReadResult = io::Result<(Vec<(Stream, String)>, Option<CommandStatus>)>;
+
+struct Pane {
     config: AppConfig,
     state: DisplayState,
+    rate: Option<RateTracker>,
+    sender: mpsc::Sender<ReadResult>,
+    results: mpsc::Receiver<ReadResult>,
+    area: Rect,
+    errors: Option<DisplayState>,
+    error_ratio: u16,
+    error_focus: bool,
+}
+
+struct App {
+    panes: Vec<Pane>,
+    focus: usize,
     terminal: Terminal<CrosstermBackend<io::Stdout>>,
+    exit_code: i32,
+    history: Vec<String>,
+    history_index: Option<usize>,
+    history_path: Option<std::path::PathBuf>,
+    resized: Arc<AtomicBool>,
 }
 
-fn parse_args() -> AppConfig {
+fn parse_args<I, T>(args: I) -> Vec<AppConfig>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
     let matches = Command::new("grain")
         .version("1.0")
         .arg(
@@ -40,7 +425,7 @@ fn parse_args() -> AppConfig {
                 .short('i')
                 .long("interval")
                 .value_name("INTERVAL")
-                .help("100ms, 1, 2s (100ms起, 默认1秒)")
+                .help("100ms, 1, 2s, 1.5m, 1m30s, 1h (100ms起, 默认1秒, 从上次读取完成时开始计算; 固定节奏请用 --precise)")
                 .default_value("1s")
         )
         .arg(
@@ -48,7 +433,23 @@ fn parse_args() -> AppConfig {
                 .short('f')
                 .long("file")
                 .value_name("FILE")
-                .help("文件 (默认: /proc/interrupts)")
+                .action(ArgAction::Append)
+                .help("文件, 可重复, 每个来源显示在单独的窗格中 (默认: 标准输入为管道时读取标准输入, 否则 /proc/interrupts)")
+        )
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .value_name("URL")
+                .action(ArgAction::Append)
+                .help("每次刷新请求 HTTP(S) 地址并显示响应内容 (-f 也接受 http:// 和 https:// 地址)")
+        )
+        .arg(
+            Arg::new("http-header")
+                .short('H')
+                .long("http-header")
+                .value_name("'K: V'")
+                .action(ArgAction::Append)
+                .help("为 HTTP 请求添加请求头 (可重复)")
         )
         .arg(
             Arg::new("command")
@@ -56,8 +457,61 @@ fn parse_args() -> AppConfig {
                 .long("command")
                 .value_name("COMMAND")
                 .num_args(1..)
-                .value_delimiter(' ')
-                .help("命令")
+                .action(ArgAction::Append)
+                .help("通过 sh -c 执行的命令 (支持引号、管道、重定向等), 可重复")
+        )
+        .arg(
+            Arg::new("shell")
+                .short('S')
+                .long("shell")
+                .action(ArgAction::SetTrue)
+                .hide(true)
+        )
+        .arg(
+            Arg::new("exec")
+                .long("exec")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("shell")
+                .help("不经过 shell, 按空格拆分命令后直接执行")
+        )
+        .arg(
+            Arg::new("chdir")
+                .long("chdir")
+                .visible_alias("cwd")
+                .value_name("DIR")
+                .help("在指定目录中执行命令")
+        )
+        .arg(
+            Arg::new("env")
+                .long("env")
+                .value_name("KEY=VALUE")
+                .action(ArgAction::Append)
+                .help("为命令设置环境变量 (可重复)")
+        )
+        .arg(
+            Arg::new("clean-env")
+                .long("clean-env")
+                .action(ArgAction::SetTrue)
+                .help("命令只使用 --env 指定的环境变量")
+        )
+        .arg(
+            Arg::new("pty")
+                .long("pty")
+                .action(ArgAction::SetTrue)
+                .help("在伪终端中运行命令 (保留颜色和按终端宽度排版的输出, 标准错误与标准输出合并)")
+        )
+        .arg(
+            Arg::new("strict-env")
+                .long("strict-env")
+                .action(ArgAction::SetTrue)
+                .help("文件路径和 --exec 参数中引用未定义的环境变量时报错 (默认原样保留)")
+        )
+        .arg(
+            Arg::new("argv")
+                .value_name("COMMAND")
+                .num_args(1..)
+                .last(true)
+                .help("-- 之后的命令及参数, 不经过 shell 原样执行 (优先于 -c)")
         )
         .arg(
             Arg::new("speed")
@@ -66,16 +520,343 @@ fn parse_args() -> AppConfig {
                 .value_name("SPEED")
                 .help("调整刷新速度倍率 (0.1-10.0)")
         )
-        .after_help(
-            "\n用法:\n  \
-              ↑/↓          垂直滚动\n  \
-              ←/→          水平滚动\n  \
-              PgUp/PgDn    垂直翻页\n  \
-              Home/End     水平跳转\n  \
-              Ctrl+Home/End   垂直跳转\n  \
-              q/Ctrl+C     退出"
+        .arg(
+            Arg::new("follow")
+                .short('F')
+                .long("follow")
+                .action(ArgAction::SetTrue)
+                .help("跟随模式: 刷新后自动滚动到底部")
+        )
+        .arg(
+            Arg::new("line-numbers")
+                .short('n')
+                .long("line-numbers")
+                .action(ArgAction::SetTrue)
+                .help("显示行号")
+        )
+        .arg(
+            Arg::new("wrap")
+                .short('w')
+                .long("wrap")
+                .action(ArgAction::SetTrue)
+                .help("自动换行显示长行 (禁用水平滚动)")
+        )
+        .arg(
+            Arg::new("no-mouse")
+                .long("no-mouse")
+                .action(ArgAction::SetTrue)
+                .help("不捕获鼠标 (保留终端自带的文本选择)")
+        )
+        .arg(
+            Arg::new("no-history")
+                .long("no-history")
+                .action(ArgAction::SetTrue)
+                .help("不把按 ! 输入的命令保存到 ~/.local/state/grain/history")
+        )
+        .arg(
+            Arg::new("no-scrollbar")
+                .long("no-scrollbar")
+                .action(ArgAction::SetTrue)
+                .help("不显示滚动条")
+        )
+        .arg(
+            Arg::new("header")
+                .long("header")
+                .value_name("N")
+                .default_value("0")
+                .value_parser(clap::value_parser!(u64))
+                .help("固定显示前 N 行作为表头, 不随垂直滚动")
+        )
+        .arg(
+            Arg::new("freeze-cols")
+                .long("freeze-cols")
+                .value_name("N")
+                .default_value("0")
+                .value_parser(clap::value_parser!(u64))
+                .help("固定左侧前 N 个字段, 不随水平滚动")
+        )
+        .arg(
+            Arg::new("sort")
+                .long("sort")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("按第 N 个字段排序 (以空白分隔, 表头不参与排序)")
+        )
+        .arg(
+            Arg::new("sort-numeric")
+                .long("sort-numeric")
+                .action(ArgAction::SetTrue)
+                .requires("sort")
+                .help("按数值排序")
+        )
+        .arg(
+            Arg::new("sort-reverse")
+                .long("sort-reverse")
+                .action(ArgAction::SetTrue)
+                .requires("sort")
+                .help("降序排序")
+        )
+        .arg(
+            Arg::new("max-bytes")
+                .long("max-bytes")
+                .value_name("SIZE")
+                .default_value("64M")
+                .help("读取 .gz/.zst 压缩文件或 URL 响应时最多读取的字节数, 如 512K、64M、1G")
+        )
+        .arg(
+            Arg::new("tabsize")
+                .long("tabsize")
+                .value_name("N")
+                .default_value("8")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("制表符宽度")
+        )
+        .arg(
+            Arg::new("head")
+                .long("head")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .conflicts_with("tail")
+                .help("只保留输出的前 N 行")
+        )
+        .arg(
+            Arg::new("tail")
+                .long("tail")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("只保留输出的最后 N 行 (同时开启跟随模式)")
+        )
+        .arg(
+            Arg::new("no-color")
+                .long("no-color")
+                .action(ArgAction::SetTrue)
+                .help("去掉输出中的 ANSI 颜色和控制序列, 以纯文本显示")
+        )
+        .arg(
+            Arg::new("no-stderr")
+                .long("no-stderr")
+                .action(ArgAction::SetTrue)
+                .help("隐藏命令的标准错误输出 (按 e 切换)")
+        )
+        .arg(
+            Arg::new("split-stderr")
+                .long("split-stderr")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("pty")
+                .help("在底部单独的窗格中显示标准错误输出 (Tab 切换焦点, [/] 调整高度)")
+        )
+        .arg(
+            Arg::new("fields")
+                .long("fields")
+                .value_name("LIST")
+                .value_delimiter(',')
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("只显示指定的字段, 如 1,3 (从 1 开始, 超出范围的字段忽略)")
+        )
+        .arg(
+            Arg::new("delimiter")
+                .long("delimiter")
+                .value_name("STR")
+                .requires("fields")
+                .help("字段分隔符 (默认按空白分隔)")
+        )
+        .arg(
+            Arg::new("filter")
+                .long("filter")
+                .value_name("PATTERN")
+                .action(ArgAction::Append)
+                .help("只显示匹配正则表达式的行 (可重复, 按顺序应用)")
+        )
+        .arg(
+            Arg::new("filter-out")
+                .long("filter-out")
+                .value_name("PATTERN")
+                .action(ArgAction::Append)
+                .help("隐藏匹配正则表达式的行 (可重复, 按顺序应用)")
+        )
+        .arg(
+            Arg::new("grep")
+                .long("grep")
+                .value_name("PATTERN")
+                .action(ArgAction::Append)
+                .help("只保留匹配正则表达式的行 (配合 -v 反选)")
+        )
+        .arg(
+            Arg::new("invert")
+                .short('v')
+                .long("invert")
+                .action(ArgAction::SetTrue)
+                .requires("grep")
+                .help("--grep 反选: 只保留不匹配的行")
+        )
+        .arg(
+            Arg::new("color-rule")
+                .long("color-rule")
+                .value_name("REGEX=COLOR")
+                .action(ArgAction::Append)
+                .help("用指定颜色显示匹配的文本 (可重复, 后面的规则优先)")
+        )
+        .arg(
+            Arg::new("status-color")
+                .long("status-color")
+                .value_name("COLOR")
+                .default_value("green")
+                .help("状态栏颜色")
+        )
+        .arg(
+            Arg::new("status-format")
+                .long("status-format")
+                .value_name("TEMPLATE")
+                .help("状态栏模板, 支持 {source} {interval} {time} {count} {uptime}")
+        )
+        .arg(
+            Arg::new("clock")
+                .long("clock")
+                .action(ArgAction::SetTrue)
+                .help("在状态栏右侧显示当前时间")
+        )
+        .arg(
+            Arg::new("no-status")
+                .long("no-status")
+                .action(ArgAction::SetTrue)
+                .help("启动时隐藏状态栏 (按 t 切换)")
+        )
+        .arg(
+            Arg::new("compute")
+                .long("compute")
+                .value_name("EXPR")
+                .help("在状态栏显示计算结果, 如 '/^MemTotal/2 - /^MemFree/2' (/正则/N 为匹配行的第 N 个字段, 支持 + - * / 和括号)")
+        )
+        .arg(
+            Arg::new("highlight")
+                .long("highlight")
+                .value_name("PATTERN")
+                .help("高亮显示匹配正则表达式的文本")
+        )
+        .arg(
+            Arg::new("differences")
+                .short('d')
+                .long("differences")
+                .visible_alias("diff")
+                .value_name("permanent")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("changes")
+                .value_parser(["changes", "permanent"])
+                .help("高亮显示两次刷新之间变化的内容 (=permanent 保留所有变化过的位置)")
+        )
+        .arg(
+            Arg::new("changes-only")
+                .long("changes-only")
+                .action(ArgAction::SetTrue)
+                .help("只显示与上次刷新相比发生变化的行")
+        )
+        .arg(
+            Arg::new("rate")
+                .long("rate")
+                .action(ArgAction::SetTrue)
+                .help("将 /proc/interrupts 中的计数显示为每秒速率")
+        )
+        .arg(
+            Arg::new("once")
+                .long("once")
+                .action(ArgAction::SetTrue)
+                .help("只读取一次并直接输出到标准输出, 不进入界面 (标准输出不是终端时自动启用)")
+        )
+        .arg(
+            Arg::new("log")
+                .long("log")
+                .value_name("FILE")
+                .help("将每次刷新的内容追加写入日志文件 (带时间戳)")
+        )
+        .arg(
+            Arg::new("snapshot-ansi")
+                .long("snapshot-ansi")
+                .action(ArgAction::SetTrue)
+                .help("按 s 保存快照时保留 ANSI 转义序列")
+        )
+        .arg(
+            Arg::new("chgexit")
+                .short('g')
+                .long("chgexit")
+                .action(ArgAction::SetTrue)
+                .help("内容发生变化时退出")
+        )
+        .arg(
+            Arg::new("equexit")
+                .long("equexit")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("内容连续 N 次刷新没有变化时退出")
+        )
+        .arg(
+            Arg::new("times")
+                .long("times")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("读取 N 次后退出 (暂停期间不计数, 手动刷新计数)")
+        )
+        .arg(
+            Arg::new("precise")
+                .short('p')
+                .long("precise")
+                .action(ArgAction::SetTrue)
+                .help("按以启动时间对齐的固定时间点刷新, 读取超时则跳过错过的时间点")
+        )
+        .arg(
+            Arg::new("overlap")
+                .long("overlap")
+                .value_name("POLICY")
+                .default_value("skip")
+                .value_parser(["skip", "wait", "kill"])
+                .help("命令运行超过间隔时: skip 跳过错过的刷新, wait 结束后立即刷新, kill 到下次刷新时终止命令")
+        )
+        .arg(
+            Arg::new("exit-on-match")
+                .long("exit-on-match")
+                .value_name("PATTERN")
+                .help("输出中出现匹配正则表达式的行时退出 (手动退出时返回 130)")
+        )
+        .arg(
+            Arg::new("bell")
+                .long("bell")
+                .visible_alias("beep")
+                .action(ArgAction::SetTrue)
+                .help("内容变化时响铃并闪烁状态栏")
+        )
+        .arg(
+            Arg::new("beep-on-match")
+                .long("beep-on-match")
+                .value_name("PATTERN")
+                .help("只在变化后的内容匹配正则表达式时响铃")
+        )
+        .arg(
+            Arg::new("confirm-quit")
+                .long("confirm-quit")
+                .action(ArgAction::SetTrue)
+                .help("按 q 退出前需要确认 (Ctrl+C 仍直接退出)")
+        )
+        .arg(
+            Arg::new("errexit")
+                .short('e')
+                .long("errexit")
+                .action(ArgAction::SetTrue)
+                .help("命令执行失败时停止刷新, 按任意键后以命令的退出码退出")
+        )
+        .arg(
+            Arg::new("keep-on-error")
+                .long("keep-on-error")
+                .action(ArgAction::SetTrue)
+                .help("命令失败或无输出时保留上一次的正常输出")
+        )
+        .arg(
+            Arg::new("timeout")
+                .long("timeout")
+                .value_name("DURATION")
+                .help("命令超时时间 (默认为间隔的 0.8 倍, 限制在 100ms-3s)")
         )
-        .get_matches();
+        .after_help(key_help())
+        .get_matches_from(args);
 
     let interval_str = matches.get_one::<String>("interval").unwrap();
     let base_interval = parse_interval(interval_str).unwrap_or_else(|e| {
@@ -83,447 +864,2571 @@ fn parse_args() -> AppConfig {
         std::process::exit(1);
     });
 
-    let interval = if let Some(speed_str) = matches.get_one::<String>("speed") {
-        let speed = speed_str.parse::<f64>().unwrap_or(1.0).clamp(0.1, 10.0);
-        Duration::from_millis((base_interval.as_millis() as f64 / speed) as u64)
-    } else {
-        base_interval
-    };
+    let speed = matches
+        .get_one::<String>("speed")
+        .map_or(1.0, |speed_str| speed_str.parse::<f64>().unwrap_or(1.0).clamp(0.1, 10.0));
+    let interval = Duration::from_millis((base_interval.as_millis() as f64 / speed) as u64);
 
-    AppConfig {
-        interval,
-        file: matches.get_one::<String>("file").map(|s| s.to_string()),
-        command: if let Some(cmd_parts) = matches.get_many::<String>("command") {
-            let parts: Vec<String> = cmd_parts.map(|s| s.to_string()).collect();
-            if !parts.is_empty() {
-                Some((parts[0].clone(), parts[1..].to_vec()))
+    let strict_env = matches.get_flag("strict-env");
+    let expand = |text: &str| {
+        expand_env(text, strict_env).unwrap_or_else(|e| {
+            eprintln!("错误: {}", e);
+            std::process::exit(1);
+        })
+    };
+    let mut sources: Vec<(usize, Source)> = Vec::new();
+    if let (Some(indices), Some(files)) = (matches.indices_of("file"), matches.get_many::<String>("file")) {
+        sources.extend(indices.zip(files).map(|(index, file)| {
+            if file.starts_with("http://") || file.starts_with("https://") {
+                (index, Source::Url(file.clone()))
             } else {
-                None
+                (index, Source::File(expand(file)))
             }
-        } else {
-            None
-        },
+        }));
     }
-}
-
-fn parse_interval(interval_str: &str) -> Result<Duration, String> {
-    let interval_str = interval_str.trim().to_lowercase();
-    
-    let (value_str, unit) = if interval_str.ends_with("ms") {
-        (&interval_str[..interval_str.len() - 2], "ms")
-    } else if interval_str.ends_with('s') {
-        (&interval_str[..interval_str.len() - 1], "s")
-    } else {
-        (&interval_str[..], "s")
-    };
-    
-    let value = value_str.parse::<f64>().map_err(|e| format!("无效的时间值: {}", e))?;
+    if let (Some(indices), Some(urls)) = (matches.indices_of("url"), matches.get_many::<String>("url")) {
+        sources.extend(indices.zip(urls).map(|(index, url)| (index, Source::Url(url.clone()))));
+    }
+    if let Some(parts) = matches.get_many::<String>("argv") {
+        let argv: Vec<String> = parts.cloned().collect();
+        let command = argv.iter().map(|arg| quote_arg(arg)).collect::<Vec<_>>().join(" ");
+        sources.push((usize::MAX, Source::Command(command, argv)));
+    } else if let (Some(mut indices), Some(occurrences)) =
+        (matches.indices_of("command"), matches.get_occurrences::<String>("command"))
+    {
+        for parts in occurrences {
+            let parts: Vec<&str> = parts.map(String::as_str).collect();
+            let index = indices.next().unwrap_or_default();
+            for _ in 1..parts.len() {
+                indices.next();
+            }
+            let command = parts.join(" ");
+            let argv = if matches.get_flag("exec") {
+                command.split_whitespace().map(expand).collect()
+            } else {
+                Vec::new()
+            };
+            sources.push((index, Source::Command(command, argv)));
+        }
+    }
+    sources.sort_by_key(|(index, _)| *index);
     
-    let ms = match unit {
-        "ms" => value as u64,
-        "s" => (value * 1000.0) as u64,
-        _ => return Err("不支持的时间单位".to_string()),
+    let config = AppConfig {
+        interval,
+        speed,
+        file: None,
+        command: None,
+        url: None,
+        headers: matches
+            .get_many::<String>("http-header")
+            .unwrap_or_default()
+            .map(|header| match header.split_once(':') {
+                Some((name, value)) if !name.trim().is_empty() => (name.trim().to_string(), value.trim().to_string()),
+                _ => {
+                    eprintln!("错误: 无效的请求头 (应为 'K: V'): {}", header);
+                    std::process::exit(1);
+                }
+            })
+            .collect(),
+        argv: Vec::new(),
+        chdir: matches.get_one::<String>("chdir").map(|dir| {
+            let dir = expand(dir);
+            if !std::path::Path::new(&dir).is_dir() {
+                eprintln!("错误: 目录不存在: {}", dir);
+                std::process::exit(1);
+            }
+            dir
+        }),
+        env: matches
+            .get_many::<String>("env")
+            .unwrap_or_default()
+            .map(|entry| match entry.split_once('=') {
+                Some((key, value)) if !key.is_empty() => (key.to_string(), value.to_string()),
+                _ => {
+                    eprintln!("错误: 无效的环境变量 (应为 KEY=VALUE): {}", entry);
+                    std::process::exit(1);
+                }
+            })
+            .collect(),
+        clean_env: matches.get_flag("clean-env"),
+        pty: matches.get_flag("pty"),
+        pty_size: crossterm::terminal::size().unwrap_or((80, 24)),
+        highlight: matches.get_one::<String>("highlight").map(|pattern| {
+            Regex::new(pattern).unwrap_or_else(|e| {
+                eprintln!("错误: 无效的高亮表达式: {}", e);
+                std::process::exit(1);
+            })
+        }),
+        compute: matches.get_one::<String>("compute").map(|expr| {
+            Expr::parse(expr).unwrap_or_else(|e| {
+                eprintln!("错误: 无效的计算表达式: {}", e);
+                std::process::exit(1);
+            })
+        }),
+        follow: matches.get_flag("follow") || matches.contains_id("tail"),
+        line_numbers: matches.get_flag("line-numbers"),
+        wrap: matches.get_flag("wrap"),
+        header: *matches.get_one::<u64>("header").unwrap() as usize,
+        freeze_cols: *matches.get_one::<u64>("freeze-cols").unwrap() as usize,
+        mouse: !matches.get_flag("no-mouse"),
+        history: !matches.get_flag("no-history"),
+        scrollbar: !matches.get_flag("no-scrollbar"),
+        tabsize: *matches.get_one::<u64>("tabsize").unwrap() as usize,
+        head: matches.get_one::<u64>("head").map(|&count| count as usize),
+        tail: matches.get_one::<u64>("tail").map(|&count| count as usize),
+        no_color: matches.get_flag("no-color"),
+        no_stderr: matches.get_flag("no-stderr"),
+        split_stderr: matches.get_flag("split-stderr"),
+        fields: matches
+            .get_many::<u64>("fields")
+            .unwrap_or_default()
+            .map(|&field| field as usize - 1)
+            .collect(),
+        delimiter: matches.get_one::<String>("delimiter").cloned(),
+        filters: parse_filters(&matches),
+        sort: matches.get_one::<u64>("sort").map(|&field| SortKey {
+            field: field as usize - 1,
+            numeric: matches.get_flag("sort-numeric"),
+            reverse: matches.get_flag("sort-reverse"),
+        }),
+        color_rules: matches
+            .get_many::<String>("color-rule")
+            .unwrap_or_default()
+            .map(|rule| {
+                ColorRule::parse(rule).unwrap_or_else(|e| {
+                    eprintln!("错误: {}", e);
+                    std::process::exit(1);
+                })
+            })
+            .collect(),
+        status_color: {
+            let color = matches.get_one::<String>("status-color").unwrap();
+            color.parse::<Color>().unwrap_or_else(|_| {
+                eprintln!("错误: 未知的颜色: {}", color);
+                std::process::exit(1);
+            })
+        },
+        status_format: matches.get_one::<String>("status-format").cloned(),
+        clock: matches.get_flag("clock"),
+        show_status: !matches.get_flag("no-status"),
+        stdin: None,
+        running: Arc::default(),
+        file_cache: Arc::default(),
+        max_bytes: parse_size(matches.get_one::<String>("max-bytes").unwrap()).unwrap_or_else(|e| {
+            eprintln!("错误: 无效的 --max-bytes: {}", e);
+            std::process::exit(1);
+        }),
+        decompressed: Arc::default(),
+        differences: match matches.get_one::<String>("differences").map(|s| s.as_str()) {
+            Some("permanent") => Some(DiffMode::Permanent),
+            Some(_) => Some(DiffMode::Changes),
+            None => None,
+        },
+        changes_only: matches.get_flag("changes-only"),
+        rate: matches.get_flag("rate"),
+        once: matches.get_flag("once"),
+        log: matches.get_one::<String>("log").map(|path| {
+            match OpenOptions::new().create(true).append(true).open(path) {
+                Ok(file) => Arc::new(Mutex::new(io::BufWriter::new(file))),
+                Err(e) => {
+                    eprintln!("错误: 无法打开日志文件 {}: {}", path, e);
+                    std::process::exit(1);
+                }
+            }
+        }),
+        snapshot_ansi: matches.get_flag("snapshot-ansi"),
+        chgexit: matches.get_flag("chgexit"),
+        errexit: matches.get_flag("errexit"),
+        keep_on_error: matches.get_flag("keep-on-error"),
+        equexit: matches.get_one::<u64>("equexit").copied(),
+        times: matches.get_one::<u64>("times").copied(),
+        precise: matches.get_flag("precise"),
+        overlap: match matches.get_one::<String>("overlap").map(|s| s.as_str()) {
+            Some("wait") => OverlapPolicy::Wait,
+            Some("kill") => OverlapPolicy::Kill,
+            _ => OverlapPolicy::Skip,
+        },
+        confirm_quit: matches.get_flag("confirm-quit"),
+        bell: matches.get_flag("bell"),
+        beep_on_match: matches.get_one::<String>("beep-on-match").map(|pattern| {
+            Regex::new(pattern).unwrap_or_else(|e| {
+                eprintln!("错误: 无效的匹配表达式: {}", e);
+                std::process::exit(1);
+            })
+        }),
+        exit_on_match: matches.get_one::<String>("exit-on-match").map(|pattern| {
+            Regex::new(pattern).unwrap_or_else(|e| {
+                eprintln!("错误: 无效的匹配表达式: {}", e);
+                std::process::exit(1);
+            })
+        }),
+        timeout: matches.get_one::<String>("timeout").map(|timeout| {
+            parse_interval(timeout).unwrap_or_else(|e| {
+                eprintln!("错误: 无效的超时时间: {}", e);
+                std::process::exit(1);
+            })
+        }),
     };
     
-    if ms < 100 {
-        return Err("间隔不能小于100毫秒".to_string());
+    if sources.is_empty() {
+        return vec![config];
     }
-    
-    Ok(Duration::from_millis(ms))
+    sources
+        .into_iter()
+        .map(|(_, source)| {
+            let mut pane = config.clone();
+            pane.running = Arc::default();
+            pane.file_cache = Arc::default();
+            pane.decompressed = Arc::default();
+            match source {
+                Source::File(file) => pane.file = Some(file),
+                Source::Url(url) => pane.url = Some(url),
+                Source::Command(command, argv) => {
+                    pane.command = Some(command);
+                    pane.argv = argv;
+                }
+            }
+            pane
+        })
+        .collect()
 }
 
-fn visual_width(line: &str) -> usize {
-    let mut in_escape = false;
-    let mut width = 0;
-    
-    for c in line.chars() {
-        if c == '\x1b' {
-            in_escape = true;
-            continue;
-        }
-        if in_escape {
-            if c == 'm' {
-                in_escape = false;
+fn key_help() -> String {
+    let lines: Vec<String> = KEY_BINDINGS
+        .iter()
+        .map(|(keys, action)| format!("  {}", key_binding_line(keys, action)))
+        .collect();
+    format!("\n用法:\n{}", lines.join("\n"))
+}
+
+fn key_binding_line(keys: &str, action: &str) -> String {
+    format!("{:<width$}{}", keys, action, width = 16 - (visual_width(keys) - keys.chars().count()))
+}
+
+fn parse_filters(matches: &clap::ArgMatches) -> Vec<LineFilter> {
+    let mut filters = Vec::new();
+    let invert = matches.get_flag("invert");
+    for (id, exclude) in [("filter", false), ("filter-out", true), ("grep", invert)] {
+        if let (Some(indices), Some(patterns)) = (matches.indices_of(id), matches.get_many::<String>(id)) {
+            for (index, pattern) in indices.zip(patterns) {
+                let regex = Regex::new(pattern).unwrap_or_else(|e| {
+                    eprintln!("错误: 无效的过滤表达式: {}", e);
+                    std::process::exit(1);
+                });
+                filters.push((index, LineFilter { regex, exclude }));
             }
-            continue;
         }
-        
-        width += 1;
     }
-    
-    width
+    filters.sort_by_key(|(index, _)| *index);
+    filters.into_iter().map(|(_, filter)| filter).collect()
 }
 
-fn crop_line_for_scroll(line: &str, scroll_x: u16) -> String {
-    if scroll_x == 0 {
-        return line.to_string();
-    }
-
-    let scroll_x_usize = scroll_x as usize;
+fn expand_env(text: &str, strict: bool) -> Result<String, String> {
     let mut result = String::new();
-    let mut in_escape = false;
-    let mut escape_buffer = String::new();
-    let mut visual_pos = 0;
-    
-    for c in line.chars() {
-        if c == '\x1b' {
-            in_escape = true;
-            escape_buffer.clear();
-            escape_buffer.push(c);
-            continue;
+    let mut rest = text;
+    if rest == "~" || rest.starts_with("~/") {
+        if let Ok(home) = std::env::var("HOME") {
+            result.push_str(&home);
+            rest = &rest[1..];
         }
-        
-        if in_escape {
-            escape_buffer.push(c);
-            if c == 'm' {
-                in_escape = false;
-                if visual_pos >= scroll_x_usize || !result.is_empty() {
-                    result.push_str(&escape_buffer);
-                }
+    }
+    
+    while let Some(index) = rest.find('$') {
+        result.push_str(&rest[..index]);
+        let after = &rest[index + 1..];
+        let (name, end) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(close) => (&braced[..close], close + 2),
+                None => ("", 0),
+            },
+            None => {
+                let len = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..len], len)
             }
+        };
+        if name.is_empty() {
+            result.push('$');
+            rest = after;
             continue;
         }
-        
-        if visual_pos >= scroll_x_usize {
-            result.push(c);
+        match std::env::var(name) {
+            Ok(value) => result.push_str(&value),
+            Err(_) if strict => return Err(format!("未定义的环境变量: {}", name)),
+            Err(_) => result.push_str(&rest[index..index + 1 + end]),
         }
-        visual_pos += 1;
+        rest = &after[end..];
     }
     
-    if result.is_empty() {
-        return String::new();
+    result.push_str(rest);
+    Ok(result)
+}
+
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|c| c.is_alphanumeric() || "-_./=:,+@%".contains(c)) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+fn parse_interval(interval_str: &str) -> Result<Duration, String> {
+    let interval_str: String = interval_str.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    if interval_str.is_empty() {
+        return Err("时间不能为空".to_string());
     }
     
-    if !result.ends_with("\x1b[0m") {
-        let mut open_escapes = 0;
-        let mut in_esc = false;
-        
-        for c in result.chars() {
-            if c == '\x1b' {
-                in_esc = true;
-                open_escapes += 1;
-            } else if in_esc && c == 'm' {
-                open_escapes -= 1;
-                if open_escapes == 0 {
-                    in_esc = false;
-                }
-            }
-        }
-        
-        if open_escapes > 0 {
-            result.push_str("\x1b[0m");
-        }
+    let total_ms = match interval_str.parse::<f64>() {
+        Ok(value) if value.is_finite() && value >= 0.0 => value * 1000.0,
+        Ok(_) => return Err(format!("无效的时间值: {}", interval_str)),
+        Err(_) => parse_duration_parts(&interval_str)?,
+    };
+    
+    let ms = total_ms.round() as u64;
+    if ms < 100 {
+        return Err("间隔不能小于100毫秒".to_string());
     }
     
-    result
+    Ok(Duration::from_millis(ms))
 }
 
-fn read_content(config: &AppConfig) -> io::Result<Vec<String>> {
-    if let Some((cmd, args)) = &config.command {
-        let mut child = ProcessCommand::new(cmd)
-            .args(args)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-        
-        let timeout = config.interval.mul_f64(0.8)
-            .max(Duration::from_millis(100))
-            .min(Duration::from_secs(3));
-        
-        let start_time = Instant::now();
+fn parse_duration_parts(text: &str) -> Result<f64, String> {
+    let is_number = |c: char| c.is_ascii_digit() || c == '.';
+    let mut rest = text;
+    let mut total_ms = 0.0;
+    
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !is_number(c)).unwrap_or(rest.len());
+        let token_end = rest[number_end..].find(is_number).map_or(rest.len(), |i| number_end + i);
+        let token = &rest[..token_end];
+        let value = rest[..number_end]
+            .parse::<f64>()
+            .map_err(|_| format!("无效的时间值: {}", token))?;
+        let scale = match &rest[number_end..token_end] {
+            "ms" => 1.0,
+            "s" => 1000.0,
+            "m" => 60_000.0,
+            "h" => 3_600_000.0,
+            "" => return Err(format!("缺少时间单位: {}", token)),
+            _ => return Err(format!("不支持的时间单位: {}", token)),
+        };
+        total_ms += value * scale;
+        rest = &rest[token_end..];
+    }
+    
+    Ok(total_ms)
+}
+
+enum AnsiToken<'a> {
+    Char(char),
+    Escape { raw: &'a str, sgr: Option<&'a str> },
+}
+
+struct AnsiTokens<'a> {
+    line: &'a str,
+    pos: usize,
+}
+
+fn ansi_tokens(line: &str) -> AnsiTokens<'_> {
+    AnsiTokens { line, pos: 0 }
+}
+
+impl<'a> Iterator for AnsiTokens<'a> {
+    type Item = AnsiToken<'a>;
+    
+    fn next(&mut self) -> Option<AnsiToken<'a>> {
+        let rest = &self.line[self.pos..];
+        let mut chars = rest.char_indices();
+        let (_, c) = chars.next()?;
+        if c != '\x1b' {
+            self.pos += c.len_utf8();
+            return Some(AnsiToken::Char(c));
+        }
         
-        loop {
-            match child.try_wait() {
-                Ok(Some(_)) => {
-                    break;
-                }
-                Ok(None) => {
-                    if start_time.elapsed() > timeout {
-                        let _ = child.kill();
-                        let _ = child.wait();
-                        break;
+        let mut sgr = None;
+        let end = match chars.next() {
+            Some((start, '[')) => {
+                let start = start + 1;
+                match chars.find(|&(_, c)| ('@'..='~').contains(&c)) {
+                    Some((end, final_byte)) => {
+                        if final_byte == 'm' {
+                            sgr = Some(&rest[start..end]);
+                        }
+                        end + 1
                     }
-                    
-                    std::thread::sleep(Duration::from_millis(10));
-                }
-                Err(e) => {
-                    return Ok(vec![format!("无法等待进程: {}", e)]);
+                    None => rest.len(),
                 }
             }
-        }
-        
-        let output = child.wait_with_output()?;
-        
-        let mut lines = Vec::new();
-        
-        if !output.stdout.is_empty() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                if !line.trim().is_empty() {
-                    lines.push(line.to_string());
+            Some((_, ']')) => {
+                let mut end = rest.len();
+                while let Some((index, c)) = chars.next() {
+                    if c == '\x07' {
+                        end = index + 1;
+                        break;
+                    }
+                    if c == '\x1b' {
+                        end = chars.next().map_or(rest.len(), |(index, c)| index + c.len_utf8());
+                        break;
+                    }
                 }
+                end
             }
-        }
-        
-        if !output.stderr.is_empty() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            for line in stderr.lines() {
-                if !line.trim().is_empty() {
-                    lines.push(format!("\x1b[31m{}\x1b[0m", line));
-                }
+            Some((_, '(' | ')')) => {
+                chars.next().map_or(rest.len(), |(index, c)| index + c.len_utf8())
             }
-        }
+            Some((index, c)) => index + c.len_utf8(),
+            None => rest.len(),
+        };
         
-        if start_time.elapsed() > timeout {
-            lines.push("\x1b[33m[超时] 进程已被强制终止\x1b[0m".to_string());
+        self.pos += end;
+        Some(AnsiToken::Escape { raw: &rest[..end], sgr })
+    }
+}
+
+fn visual_width(line: &str) -> usize {
+    ansi_tokens(line)
+        .map(|token| match token {
+            AnsiToken::Char(c) => c.width().unwrap_or(0),
+            AnsiToken::Escape { .. } => 0,
+        })
+        .sum()
+}
+
+fn truncate_to_width(text: &str, max_width: usize) -> String {
+    let mut result = String::new();
+    let mut width = 0;
+
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if width + char_width > max_width {
+            break;
         }
-        
-        if lines.is_empty() {
-            lines.push("命令无输出".to_string());
+        width += char_width;
+        result.push(c);
+    }
+
+    result
+}
+
+fn select_fields(line: &str, fields: &[usize], delimiter: Option<&str>) -> String {
+    let parts: Vec<&str> = match delimiter {
+        Some(delimiter) => line.split(delimiter).collect(),
+        None => line.split_whitespace().collect(),
+    };
+    fields
+        .iter()
+        .filter_map(|&field| parts.get(field).copied())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn expand_tabs(line: &str, tabsize: usize) -> String {
+    if !line.contains('\t') {
+        return line.to_string();
+    }
+
+    let mut result = String::new();
+    let mut column = 0;
+
+    for token in ansi_tokens(line) {
+        match token {
+            AnsiToken::Char('\t') => {
+                let spaces = tabsize - column % tabsize;
+                result.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            }
+            AnsiToken::Char(c) => {
+                column += c.width().unwrap_or(0);
+                result.push(c);
+            }
+            AnsiToken::Escape { raw, .. } => result.push_str(raw),
         }
-        
-        Ok(lines)
-    } else if let Some(file_path) = &config.file {
-        let file = File::open(file_path)?;
-        let reader = BufReader::new(file);
-        let mut lines = Vec::new();
-        for line_res in reader.lines() {
-            match line_res {
-                Ok(line) => {
-                    if !line.trim().is_empty() {
-                        lines.push(line);
-                    }
-                }
-                Err(e) => eprintln!("读取行失败: {}", e),
+    }
+
+    result
+}
+
+fn crop_line_for_scroll(line: Line<'static>, scroll_x: u16) -> Line<'static> {
+    if scroll_x == 0 {
+        return line;
+    }
+
+    let scroll_x = scroll_x as usize;
+    let mut spans = Vec::new();
+    let mut visual_pos = 0;
+    
+    for span in line.spans {
+        let mut text = String::new();
+        for c in span.content.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if visual_pos >= scroll_x {
+                text.push(c);
+            } else if visual_pos + char_width > scroll_x {
+                text.extend(std::iter::repeat_n(' ', visual_pos + char_width - scroll_x));
             }
+            visual_pos += char_width;
         }
-        if lines.is_empty() {
-            lines.push(format!("文件 {} 为空", file_path));
+        if !text.is_empty() {
+            spans.push(Span::styled(text, span.style));
         }
-        Ok(lines)
-    } else {
-        let file = File::open("/proc/interrupts")?;
-        let reader = BufReader::new(file);
-        let mut lines = Vec::new();
-        for line_res in reader.lines() {
-            match line_res {
-                Ok(line) => {
-                    if !line.trim().is_empty() {
-                        lines.push(line);
-                    }
+    }
+    
+    Line::from(spans)
+}
+
+const WRAP_MARKER: &str = "↪ ";
+
+fn wrap_line(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let marker_width = visual_width(WRAP_MARKER);
+    let mut rows = vec![Vec::new()];
+    let mut row_width = 0;
+
+    for span in line.spans {
+        let mut text = String::new();
+        for c in span.content.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if row_width + char_width > width && row_width > marker_width {
+                if !text.is_empty() {
+                    rows.last_mut().unwrap().push(Span::styled(std::mem::take(&mut text), span.style));
                 }
-                Err(e) => eprintln!("读取行失败: {}", e),
+                rows.push(vec![Span::styled(WRAP_MARKER, Style::default().fg(Color::DarkGray))]);
+                row_width = marker_width;
             }
+            text.push(c);
+            row_width += char_width;
         }
-        if lines.is_empty() {
-            lines.push("/proc/interrupts 为空".to_string());
+        if !text.is_empty() {
+            rows.last_mut().unwrap().push(Span::styled(text, span.style));
         }
-        Ok(lines)
     }
-}
 
-struct DisplayState {
-    scroll_y: u16,
-    scroll_x: u16,
-    content: Vec<String>,
-    last_update: Instant,
-    last_render: Instant,
+    rows.into_iter().map(Line::from).collect()
 }
 
-impl DisplayState {
-    fn new() -> Self {
-        Self {
-            scroll_y: 0,
-            scroll_x: 0,
-            content: Vec::new(),
-            last_update: Instant::now(),
-            last_render: Instant::now(),
-        }
-    }
+fn wrap_row_count(line: &str, width: usize) -> usize {
+    let marker_width = visual_width(WRAP_MARKER);
+    let mut rows = 1;
+    let mut row_width = 0;
 
-    fn update_content(&mut self, new_content: Vec<String>, width: u16, height: u16) {
-        if new_content != self.content {
-            let max_scroll_y = new_content.len().saturating_sub(height as usize) as u16;
-            self.scroll_y = self.scroll_y.min(max_scroll_y);
-            
-            let max_scroll_x = new_content
-                .iter()
-                .map(|line| visual_width(line) as u16)
-                .max()
-                .unwrap_or(0)
-                .saturating_sub(width)
-                .max(0);
-            self.scroll_x = self.scroll_x.min(max_scroll_x);
-            
-            self.content = new_content;
+    for c in strip_ansi(line).chars() {
+        let char_width = c.width().unwrap_or(0);
+        if row_width + char_width > width && row_width > marker_width {
+            rows += 1;
+            row_width = marker_width;
         }
+        row_width += char_width;
     }
-    
-    fn get_display_text(&self, _width: u16, height: u16) -> Text<'static> {
-        let start_y = self.scroll_y as usize;
-        let end_y = (start_y + height as usize).min(self.content.len());
-        
-        if start_y >= end_y {
-            return Text::from("没有内容可显示");
+
+    rows
+}
+
+fn truncate_line(line: Line<'static>, width: usize) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut visual_pos = 0;
+
+    'spans: for span in line.spans {
+        let mut text = String::new();
+        for c in span.content.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if visual_pos + char_width > width {
+                if !text.is_empty() {
+                    spans.push(Span::styled(text, span.style));
+                }
+                break 'spans;
+            }
+            text.push(c);
+            visual_pos += char_width;
         }
-        
-        let mut lines = Vec::new();
-        
-        for line in &self.content[start_y..end_y] {
-            let cropped_line = crop_line_for_scroll(line, self.scroll_x);
-            let line_str = if cropped_line.is_empty() {
-                "".to_string()
-            } else {
-                cropped_line
-            };
-            lines.push(Line::from(line_str));
+        if !text.is_empty() {
+            spans.push(Span::styled(text, span.style));
         }
-        
-        Text::from(lines)
     }
 
-    fn handle_key_event(
-        &mut self,
-        key_event: &KeyEvent,
-        width: u16,
-        height: u16,
-    ) -> bool {
-        if key_event.kind != KeyEventKind::Press {
-            return false;
-        }
-        
-        let max_scroll_y = self.content.len().saturating_sub(height as usize) as u16;
-        let max_scroll_x = self.content
-            .iter()
-            .map(|line| visual_width(line) as u16)
-            .max()
-            .unwrap_or(0)
-            .saturating_sub(width)
-            .max(0);
-        
-        match key_event.code {
-            KeyCode::Up => {
-                self.scroll_y = self.scroll_y.saturating_sub(1);
-                true
-            }
-            KeyCode::Down => {
-                self.scroll_y = (self.scroll_y + 1).min(max_scroll_y);
-                true
-            }
-            
-            KeyCode::PageUp => {
-                self.scroll_y = self.scroll_y.saturating_sub(height);
-                true
-            }
-            KeyCode::PageDown => {
-                self.scroll_y = (self.scroll_y + height).min(max_scroll_y);
-                true
-            }
-            
-            KeyCode::Left => {
-                self.scroll_x = self.scroll_x.saturating_sub(1);
-                true
-            }
-            KeyCode::Right => {
-                self.scroll_x = (self.scroll_x + 1).min(max_scroll_x);
-                true
-            }
-            
-            KeyCode::Home if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.scroll_y = 0;
-                true
-            }
-            KeyCode::End if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.scroll_y = max_scroll_y;
-                true
-            }
-            
-            KeyCode::Home => {
-                self.scroll_x = 0;
-                true
-            }
-            KeyCode::End => {
-                self.scroll_x = max_scroll_x;
-                true
+    if visual_pos < width {
+        spans.push(Span::raw(" ".repeat(width - visual_pos)));
+    }
+
+    Line::from(spans)
+}
+
+fn field_end_column(line: &str, fields: usize) -> usize {
+    let mut column = 0;
+    let mut seen = 0;
+    let mut in_field = false;
+
+    for c in strip_ansi(line).chars() {
+        if c.is_whitespace() {
+            if in_field {
+                in_field = false;
+                if seen == fields {
+                    return column;
+                }
             }
-            
-            _ => false,
+        } else if !in_field {
+            in_field = true;
+            seen += 1;
         }
-    }
-    
-    fn mark_rendered(&mut self) {
-        self.last_render = Instant::now();
+        column += c.width().unwrap_or(0);
     }
 
-    fn should_update(&self, interval: Duration) -> bool {
-        let now = Instant::now();
-        now.duration_since(self.last_update) >= interval
+    column
+}
+
+fn ansi_color(code: u16) -> Color {
+    match code {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        _ => Color::Gray,
     }
-    
-    fn mark_updated(&mut self) {
-        self.last_update = Instant::now();
+}
+
+fn ansi_bright_color(code: u16) -> Color {
+    match code {
+        0 => Color::DarkGray,
+        1 => Color::LightRed,
+        2 => Color::LightGreen,
+        3 => Color::LightYellow,
+        4 => Color::LightBlue,
+        5 => Color::LightMagenta,
+        6 => Color::LightCyan,
+        _ => Color::White,
     }
 }
 
-fn format_interval(interval: Duration) -> String {
-    let ms = interval.as_millis();
-    if ms % 1000 == 0 {
-        format!("{}s", ms / 1000)
-    } else {
-        format!("{}ms", ms)
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    if params.is_empty() {
+        return Style::default();
+    }
+
+    let mut codes = params.split(';').map(|p| p.parse::<u16>().unwrap_or(0));
+    while let Some(code) = codes.next() {
+        style = match code {
+            0 => Style::default(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            25 => style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            29 => style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg(ansi_color(code - 30)),
+            38 | 48 => {
+                let color = match codes.next() {
+                    Some(5) => codes.next().map(|index| Color::Indexed(index as u8)),
+                    Some(2) => match (codes.next(), codes.next(), codes.next()) {
+                        (Some(r), Some(g), Some(b)) => Some(Color::Rgb(r as u8, g as u8, b as u8)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                match color {
+                    Some(color) if code == 38 => style.fg(color),
+                    Some(color) => style.bg(color),
+                    None => style,
+                }
+            }
+            39 => Style { fg: None, ..style },
+            40..=47 => style.bg(ansi_color(code - 40)),
+            49 => Style { bg: None, ..style },
+            90..=97 => style.fg(ansi_bright_color(code - 90)),
+            100..=107 => style.bg(ansi_bright_color(code - 100)),
+            _ => style,
+        };
     }
+
+    style
 }
 
-fn get_status_line(config: &AppConfig, _state: &DisplayState, width: u16, _height: u16) -> Line<'static> {
-    let source = if let Some((cmd, args)) = &config.command {
-        let full_cmd = format!("{} {}", cmd, args.join(" "));
-        let max_len = (width as usize).saturating_sub(10);
-        if full_cmd.len() > max_len {
-            let truncated = &full_cmd[..max_len];
-            format!("{}...", truncated)
-        } else {
-            full_cmd
+fn parse_ansi_line(line: &str) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut style = Style::default();
+    let mut text = String::new();
+
+    for token in ansi_tokens(line) {
+        match token {
+            AnsiToken::Char(c) => text.push(c),
+            AnsiToken::Escape { sgr: Some(params), .. } => {
+                if !text.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut text), style));
+                }
+                style = apply_sgr(style, params);
+            }
+            AnsiToken::Escape { .. } => {}
         }
-    } else if let Some(file) = &config.file {
+    }
+
+    if !text.is_empty() {
+        spans.push(Span::styled(text, style));
+    }
+
+    Line::from(spans)
+}
+
+fn strip_ansi(line: &str) -> String {
+    ansi_tokens(line)
+        .filter_map(|token| match token {
+            AnsiToken::Char(c) => Some(c),
+            AnsiToken::Escape { .. } => None,
+        })
+        .collect()
+}
+
+fn patch_line_style(line: Line<'static>, ranges: &[(usize, usize)], patch: Style) -> Line<'static> {
+    if ranges.is_empty() {
+        return line;
+    }
+
+    let mut spans = Vec::new();
+    let mut pos = 0;
+
+    for span in line.spans {
+        let mut text = String::new();
+        let mut patched = false;
+
+        for c in span.content.chars() {
+            let hit = ranges.iter().any(|&(start, end)| pos >= start && pos < end);
+            if hit != patched && !text.is_empty() {
+                let style = if patched { span.style.patch(patch) } else { span.style };
+                spans.push(Span::styled(std::mem::take(&mut text), style));
+            }
+            patched = hit;
+            text.push(c);
+            pos += c.width().unwrap_or(0);
+        }
+
+        if !text.is_empty() {
+            let style = if patched { span.style.patch(patch) } else { span.style };
+            spans.push(Span::styled(text, style));
+        }
+    }
+
+    Line::from(spans)
+}
+
+fn column_range(text: &str, byte_start: usize, byte_end: usize) -> (usize, usize) {
+    let start = visual_width(&text[..byte_start]);
+    (start, start + visual_width(&text[byte_start..byte_end]))
+}
+
+fn find_ranges(text: &str, pattern: &str) -> Vec<(usize, usize)> {
+    text.match_indices(pattern)
+        .map(|(start, found)| column_range(text, start, start + found.len()))
+        .collect()
+}
+
+fn regex_ranges(text: &str, regex: &Regex) -> Vec<(usize, usize)> {
+    regex
+        .find_iter(text)
+        .map(|found| column_range(text, found.start(), found.end()))
+        .collect()
+}
+
+fn true_runs(cells: impl Iterator<Item = bool>) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    let mut len = 0;
+
+    for (i, cell) in cells.enumerate() {
+        match (cell, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                ranges.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+        len = i + 1;
+    }
+    if let Some(s) = start {
+        ranges.push((s, len));
+    }
+
+    ranges
+}
+
+fn changed_chars(old_line: &str, new_line: &str) -> Vec<(usize, usize)> {
+    let old_chars: Vec<char> = strip_ansi(old_line).chars().collect();
+    let mut columns = Vec::new();
+
+    for (i, c) in strip_ansi(new_line).chars().enumerate() {
+        let changed = old_chars.get(i) != Some(&c);
+        columns.extend(std::iter::repeat_n(changed, c.width().unwrap_or(0)));
+    }
+
+    true_runs(columns.into_iter())
+}
+
+fn diff_lines(old_content: &[String], new_content: &[String]) -> Vec<Vec<(usize, usize)>> {
+    new_content
+        .iter()
+        .enumerate()
+        .map(|(i, line)| match old_content.get(i) {
+            Some(old_line) => changed_chars(old_line, line),
+            None => vec![(0, visual_width(line))],
+        })
+        .collect()
+}
+
+fn mark_dirty(dirty: &mut Vec<Vec<bool>>, changes: &[Vec<(usize, usize)>]) {
+    if dirty.len() < changes.len() {
+        dirty.resize(changes.len(), Vec::new());
+    }
+
+    for (row, ranges) in dirty.iter_mut().zip(changes) {
+        for &(start, end) in ranges {
+            if row.len() < end {
+                row.resize(end, false);
+            }
+            row[start..end].iter_mut().for_each(|cell| *cell = true);
+        }
+    }
+}
+
+fn dirty_ranges(dirty: &[Vec<bool>]) -> Vec<Vec<(usize, usize)>> {
+    dirty
+        .iter()
+        .map(|row| true_runs(row.iter().copied()))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CommandExit {
+    Code(i32),
+    Killed,
+    Timeout,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CommandStatus {
+    exit: CommandExit,
+    duration: Duration,
+    has_output: bool,
+}
+
+fn signal_process_group(pid: u32, force: bool) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), if force { libc::SIGKILL } else { libc::SIGTERM });
+    }
+    #[cfg(not(unix))]
+    let _ = (pid, force);
+}
+
+fn terminate(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        signal_process_group(child.id(), false);
+        let deadline = Instant::now() + KILL_GRACE;
+        while Instant::now() < deadline {
+            if !matches!(child.try_wait(), Ok(None)) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+    signal_process_group(child.id(), true);
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(unix)]
+fn attach_pty(process: &mut ProcessCommand, (width, height): (u16, u16)) -> io::Result<File> {
+    use std::os::unix::io::FromRawFd;
+    
+    let mut master = 0;
+    let mut slave = 0;
+    let mut size = libc::winsize { ws_row: height, ws_col: width, ws_xpixel: 0, ws_ypixel: 0 };
+    let result = unsafe {
+        libc::openpty(&mut master, &mut slave, std::ptr::null_mut(), std::ptr::null_mut(), std::ptr::addr_of_mut!(size))
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    unsafe {
+        libc::fcntl(master, libc::F_SETFD, libc::FD_CLOEXEC);
+        libc::fcntl(slave, libc::F_SETFD, libc::FD_CLOEXEC);
+    }
+    let (master, slave) = unsafe { (File::from_raw_fd(master), File::from_raw_fd(slave)) };
+    process
+        .stdin(slave.try_clone()?)
+        .stdout(slave.try_clone()?)
+        .stderr(slave);
+    unsafe {
+        std::os::unix::process::CommandExt::pre_exec(process, || {
+            if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    Ok(master)
+}
+
+#[cfg(not(unix))]
+fn attach_pty(_process: &mut ProcessCommand, _size: (u16, u16)) -> io::Result<File> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "当前平台不支持伪终端"))
+}
+
+fn spawn_line_reader(reader: impl Read + Send + 'static, is_stderr: bool, sender: mpsc::Sender<(bool, String)>) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut buffer = Vec::new();
+        loop {
+            buffer.clear();
+            let result = reader.read_until(b'\n', &mut buffer);
+            if !buffer.is_empty() {
+                let line = String::from_utf8_lossy(&buffer);
+                let line = line.trim_end_matches(['\n', '\r']).to_string();
+                if sender.send((is_stderr, line)).is_err() {
+                    break;
+                }
+            }
+            if !matches!(result, Ok(n) if n > 0) {
+                break;
+            }
+        }
+    });
+}
+
+fn stdout_lines(lines: Vec<String>) -> Vec<(Stream, String)> {
+    lines.into_iter().map(|line| (Stream::Stdout, line)).collect()
+}
+
+#[derive(Debug)]
+struct FileCache {
+    id: u64,
+    size: u64,
+    modified: Option<SystemTime>,
+    len: u64,
+    tail: Vec<u8>,
+    lines: Vec<String>,
+}
+
+impl FileCache {
+    fn reusable(&self, file: &mut File, id: u64, metadata: &std::fs::Metadata) -> io::Result<bool> {
+        let grew = metadata.len() > self.size;
+        let unchanged = metadata.len() == self.size && metadata.modified().ok() == self.modified;
+        if self.id != id || !(grew || unchanged) {
+            return Ok(false);
+        }
+        let mut tail = vec![0; self.tail.len()];
+        file.seek(SeekFrom::Start(self.len - self.tail.len() as u64))?;
+        file.read_exact(&mut tail)?;
+        Ok(tail == self.tail)
+    }
+}
+
+fn file_id(metadata: &std::fs::Metadata) -> u64 {
+    #[cfg(unix)]
+    return std::os::unix::fs::MetadataExt::ino(metadata);
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        0
+    }
+}
+
+fn push_lines(lines: &mut Vec<String>, bytes: &[u8]) {
+    for line in bytes.split(|&byte| byte == b'\n') {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches('\r');
+        if !line.trim().is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    fn detect(path: &str, magic: &[u8]) -> Option<Self> {
+        if path.ends_with(".gz") || magic.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if path.ends_with(".zst") || magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+}
+
+fn read_compressed(config: &AppConfig, path: &str, file: File, compression: Compression) -> io::Result<Vec<String>> {
+    let decoder: Box<dyn Read> = match compression {
+        Compression::Gzip => Box::new(GzDecoder::new(file)),
+        Compression::Zstd => Box::new(zstd::Decoder::new(file)?),
+    };
+    let mut buffer = Vec::new();
+    decoder
+        .take(config.max_bytes.saturating_add(1))
+        .read_to_end(&mut buffer)
+        .map_err(|e| io::Error::new(e.kind(), format!("{} 解压失败 ({}): {}", path, compression.label(), e)))?;
+    let truncated = buffer.len() as u64 > config.max_bytes;
+    buffer.truncate(config.max_bytes as usize);
+    *config.decompressed.lock().unwrap() = Some((compression, buffer.len() as u64));
+    
+    let mut lines = Vec::new();
+    push_lines(&mut lines, &buffer);
+    if truncated {
+        lines.push(truncated_notice("解压后超过", config.max_bytes));
+    }
+    Ok(lines)
+}
+
+fn truncated_notice(what: &str, max_bytes: u64) -> String {
+    format!(
+        "\x1b[33m[已截断] {} {}, 只显示前面的部分 (可用 --max-bytes 调整)\x1b[0m",
+        what,
+        format_size(max_bytes)
+    )
+}
+
+fn read_file(config: &AppConfig, path: &str) -> io::Result<Vec<String>> {
+    let mut file = File::open(path)?;
+    let mut magic = [0; 4];
+    let count = file.read(&mut magic)?;
+    file.seek(SeekFrom::Start(0))?;
+    if let Some(compression) = Compression::detect(path, &magic[..count]) {
+        return read_compressed(config, path, file, compression);
+    }
+    *config.decompressed.lock().unwrap() = None;
+    
+    let metadata = file.metadata()?;
+    let id = file_id(&metadata);
+    let trusted = !path.starts_with("/proc/") && !path.starts_with("/sys/");
+    let mut cached = config.file_cache.lock().unwrap();
+    let mut cache = match cached.take() {
+        Some(previous) if trusted && previous.reusable(&mut file, id, &metadata)? => previous,
+        _ => FileCache { id, size: 0, modified: None, len: 0, tail: Vec::new(), lines: Vec::new() },
+    };
+    
+    let mut buffer = Vec::new();
+    file.seek(SeekFrom::Start(cache.len))?;
+    file.read_to_end(&mut buffer)?;
+    let read_size = cache.len + buffer.len() as u64;
+    let complete = buffer.iter().rposition(|&byte| byte == b'\n').map_or(0, |index| index + 1);
+    push_lines(&mut cache.lines, &buffer[..complete]);
+    if let Some(count) = config.tail {
+        cache.lines.drain(..cache.lines.len().saturating_sub(count));
+    }
+    cache.len += complete as u64;
+    cache.tail.extend_from_slice(&buffer[..complete]);
+    cache.tail.drain(..cache.tail.len().saturating_sub(FILE_CHECK_BYTES));
+    cache.size = metadata.len();
+    cache.modified = metadata.modified().ok();
+    
+    let mut lines = cache.lines.clone();
+    push_lines(&mut lines, &buffer[complete..]);
+    if trusted && read_size >= metadata.len() {
+        *cached = Some(cache);
+    }
+    Ok(lines)
+}
+
+fn read_timeout(config: &AppConfig) -> Duration {
+    let timeout = config.timeout.unwrap_or_else(|| {
+        config.interval.mul_f64(0.8)
+            .max(Duration::from_millis(100))
+            .min(Duration::from_secs(3))
+    });
+    if config.overlap == OverlapPolicy::Kill {
+        timeout.min(config.interval)
+    } else {
+        timeout
+    }
+}
+
+fn read_url(config: &AppConfig, url: &str) -> io::Result<Vec<String>> {
+    let agent = ureq::AgentBuilder::new().timeout(read_timeout(config)).build();
+    let mut request = agent.get(url);
+    for (name, value) in &config.headers {
+        request = request.set(name, value);
+    }
+    let (failed, response) = match request.call() {
+        Ok(response) => (false, response),
+        Err(ureq::Error::Status(_, response)) => (true, response),
+        Err(ureq::Error::Transport(e)) => return Err(io::Error::other(e.to_string())),
+    };
+    let status = format!("HTTP {} {}", response.status(), response.status_text());
+    let mut buffer = Vec::new();
+    response.into_reader().take(config.max_bytes.saturating_add(1)).read_to_end(&mut buffer)?;
+    let truncated = buffer.len() as u64 > config.max_bytes;
+    buffer.truncate(config.max_bytes as usize);
+    
+    let mut lines = Vec::new();
+    if failed {
+        lines.push(format!("\x1b[1;37;41m {} \x1b[0m", status));
+    }
+    push_lines(&mut lines, &buffer);
+    if truncated {
+        lines.push(truncated_notice("响应超过", config.max_bytes));
+    }
+    if lines.is_empty() {
+        lines.push(format!("{} (响应为空)", status));
+    }
+    Ok(lines)
+}
+
+fn read_source(config: &AppConfig) -> ReadResult {
+    if let Some(cmd) = &config.command {
+        let mut process = if let Some((program, args)) = config.argv.split_first() {
+            let mut process = ProcessCommand::new(program);
+            process.args(args);
+            process
+        } else if cfg!(windows) {
+            let mut process = ProcessCommand::new("cmd");
+            process.arg("/C").arg(cmd);
+            process
+        } else {
+            let mut process = ProcessCommand::new("sh");
+            process.arg("-c").arg(cmd);
+            process
+        };
+        if let Some(dir) = &config.chdir {
+            process.current_dir(dir);
+        }
+        if config.clean_env {
+            process.env_clear();
+        }
+        process.envs(config.env.iter().map(|(key, value)| (key, value)));
+        let pty = if config.pty {
+            Some(attach_pty(&mut process, config.pty_size)?)
+        } else {
+            #[cfg(unix)]
+            std::os::unix::process::CommandExt::process_group(&mut process, 0);
+            process.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+            None
+        };
+        let mut child = process.spawn()?;
+        drop(process);
+        *config.running.lock().unwrap() = Some(child.id());
+        let (sender, receiver) = mpsc::channel();
+        if let Some(master) = pty {
+            spawn_line_reader(master, false, sender.clone());
+        }
+        if let Some(stdout) = child.stdout.take() {
+            spawn_line_reader(stdout, false, sender.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            spawn_line_reader(stderr, true, sender.clone());
+        }
+        drop(sender);
+        
+        let timeout = read_timeout(config);
+        
+        let start_time = Instant::now();
+        let mut timed_out = false;
+        
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => {
+                    break;
+                }
+                Ok(None) => {
+                    if start_time.elapsed() > timeout {
+                        terminate(&mut child);
+                        timed_out = true;
+                        break;
+                    }
+                    
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                Err(e) => {
+                    return Ok((vec![(Stream::Stdout, format!("无法等待进程: {}", e))], None));
+                }
+            }
+        }
+        
+        let exit_status = child.wait()?;
+        let output: Vec<(bool, String)> = receiver.iter().collect();
+        *config.running.lock().unwrap() = None;
+        let status = CommandStatus {
+            exit: match exit_status.code() {
+                _ if timed_out => CommandExit::Timeout,
+                Some(code) => CommandExit::Code(code),
+                None => CommandExit::Killed,
+            },
+            duration: start_time.elapsed(),
+            has_output: output.iter().any(|(is_stderr, line)| !is_stderr && !line.trim().is_empty()),
+        };
+        
+        let mut lines = Vec::new();
+        
+        for (is_stderr, line) in output {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if is_stderr {
+                lines.push((Stream::Stderr, format!("\x1b[31m{}\x1b[0m", line)));
+            } else {
+                lines.push((Stream::Stdout, line));
+            }
+        }
+        
+        if timed_out {
+            let captured = if status.has_output { "以上为已捕获的部分输出" } else { "没有捕获到输出" };
+            lines.push((Stream::Stdout, format!(
+                "\x1b[33m[超时] 命令运行超过 {} 已被终止, {}\x1b[0m",
+                format_interval(timeout),
+                captured
+            )));
+        }
+        
+        if lines.is_empty() {
+            lines.push((Stream::Stdout, "命令无输出".to_string()));
+        }
+        
+        Ok((lines, Some(status)))
+    } else if let Some(url) = &config.url {
+        Ok((stdout_lines(read_url(config, url)?), None))
+    } else if let Some(file_path) = &config.file {
+        let mut lines = read_file(config, file_path)?;
+        if lines.is_empty() {
+            lines.push(format!("文件 {} 为空", file_path));
+        }
+        Ok((stdout_lines(lines), None))
+    } else if let Some(buffer) = &config.stdin {
+        let lines = buffer.lock().unwrap().clone();
+        if lines.is_empty() {
+            return Ok((stdout_lines(vec!["等待标准输入...".to_string()]), None));
+        }
+        Ok((stdout_lines(lines), None))
+    } else {
+        let file = File::open("/proc/interrupts")?;
+        let reader = BufReader::new(file);
+        let mut lines = Vec::new();
+        for line_res in reader.lines() {
+            match line_res {
+                Ok(line) => {
+                    if !line.trim().is_empty() {
+                        lines.push(line);
+                    }
+                }
+                Err(e) => eprintln!("读取行失败: {}", e),
+            }
+        }
+        if lines.is_empty() {
+            lines.push("/proc/interrupts 为空".to_string());
+        }
+        Ok((stdout_lines(lines), None))
+    }
+}
+
+fn read_content(config: &AppConfig) -> ReadResult {
+    let (mut lines, status) = read_source(config)?;
+    if let Some(count) = config.head {
+        lines.truncate(count);
+    }
+    if let Some(count) = config.tail {
+        lines.drain(..lines.len().saturating_sub(count));
+    }
+    let lines = lines
+        .into_iter()
+        .map(|(stream, line)| (stream, if config.no_color { strip_ansi(&line) } else { line }))
+        .map(|(stream, line)| {
+            if config.fields.is_empty() {
+                (stream, expand_tabs(&line, config.tabsize))
+            } else {
+                (stream, expand_tabs(&select_fields(&line, &config.fields, config.delimiter.as_deref()), config.tabsize))
+            }
+        })
+        .collect();
+    Ok((lines, status))
+}
+
+#[derive(Default)]
+struct RateTracker {
+    last_read: Option<Instant>,
+    counters: HashMap<String, Vec<u64>>,
+}
+
+impl RateTracker {
+    fn apply(&mut self, content: Vec<String>) -> Vec<String> {
+        let now = Instant::now();
+        let elapsed = self.last_read.map(|last| now.duration_since(last).as_secs_f64());
+        self.last_read = Some(now);
+
+        let mut counters = HashMap::new();
+        let lines = content
+            .into_iter()
+            .map(|line| {
+                let Some((label, rest)) = line.split_once(':') else {
+                    return line;
+                };
+                let fields = counter_fields(rest);
+                if fields.is_empty() {
+                    return line;
+                }
+                let previous = self.counters.get(label.trim());
+                let mut result = format!("{}:", label);
+                for (i, &(start, end, value)) in fields.iter().enumerate() {
+                    let rate = match (elapsed, previous.and_then(|counts| counts.get(i))) {
+                        (Some(secs), Some(&old)) if secs > 0.0 => {
+                            format!("{:.0}", value.saturating_sub(old) as f64 / secs)
+                        }
+                        _ => "-".to_string(),
+                    };
+                    result.push_str(&format!(" {:>width$}", rate, width = (end - start).saturating_sub(1)));
+                }
+                result.push_str(&rest[fields[fields.len() - 1].1..]);
+                counters.insert(label.trim().to_string(), fields.iter().map(|&(_, _, value)| value).collect());
+                result
+            })
+            .collect();
+        self.counters = counters;
+        lines
+    }
+}
+
+fn counter_fields(text: &str) -> Vec<(usize, usize, u64)> {
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    loop {
+        let rest = &text[pos..];
+        let token_start = rest.len() - rest.trim_start().len();
+        if token_start == 0 && pos > 0 {
+            break;
+        }
+        let token = rest[token_start..].split_whitespace().next().unwrap_or("");
+        match token.parse::<u64>() {
+            Ok(value) if token.bytes().all(|b| b.is_ascii_digit()) => {
+                let end = pos + token_start + token.len();
+                fields.push((pos, end, value));
+                pos = end;
+            }
+            _ => break,
+        }
+    }
+    fields
+}
+
+fn spawn_stdin_reader() -> Arc<Mutex<Vec<String>>> {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let shared = Arc::clone(&buffer);
+
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+            match line {
+                Ok(line) => {
+                    if !line.trim().is_empty() {
+                        shared.lock().unwrap().push(line);
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    });
+
+    buffer
+}
+
+const MOUSE_SCROLL_STEP: i32 = 3;
+const MIN_STATUS_WIDTH: usize = 24;
+const BELL_FLASH: Duration = Duration::from_millis(300);
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+const MAX_INTERVAL: Duration = Duration::from_secs(3600);
+const KILL_GRACE: Duration = Duration::from_millis(500);
+const SPINNER_DELAY: Duration = Duration::from_millis(150);
+const SPINNER: &[char] = &['|', '/', '-', '\\'];
+const INTERVAL_STEP: f64 = 1.5;
+const ERROR_RATIO_STEP: u16 = 5;
+const HISTORY_LIMIT: usize = 200;
+const FILE_CHECK_BYTES: usize = 256;
+const ROW_CACHE_WIDTHS: usize = 4;
+
+struct DisplayState {
+    scroll_y: u16,
+    scroll_x: u16,
+    content: Vec<String>,
+    header: Vec<String>,
+    header_lines: usize,
+    freeze_cols: usize,
+    max_width: usize,
+    max_frozen: usize,
+    row_starts: RefCell<HashMap<usize, Vec<usize>>>,
+    line_numbers: Vec<usize>,
+    source_content: Vec<String>,
+    source_streams: Vec<Stream>,
+    hide_stderr: bool,
+    previous_source: Vec<String>,
+    changes_only: bool,
+    filters: Vec<LineFilter>,
+    sort: Option<SortKey>,
+    color_rules: Vec<ColorRule>,
+    highlight: Option<Regex>,
+    message: Option<(String, Color)>,
+    snapshot_ansi: bool,
+    last_update: Instant,
+    read_started: Instant,
+    precise: bool,
+    drift: Duration,
+    overlap: OverlapPolicy,
+    skipped: u64,
+    started: Instant,
+    refresh_count: u64,
+    computed: Option<Result<f64, String>>,
+    stable_count: u64,
+    bell_at: Option<Instant>,
+    command_status: Option<CommandStatus>,
+    failed: Option<i32>,
+    stale: bool,
+    show_status: bool,
+    reading: bool,
+    last_error: Option<String>,
+    file_missing: bool,
+    has_good_content: bool,
+    last_render: Instant,
+    paused: bool,
+    follow: bool,
+    follow_disengaged: bool,
+    show_line_numbers: bool,
+    show_help: bool,
+    confirming_quit: bool,
+    help_scroll: u16,
+    scrollbar: bool,
+    wrap: bool,
+    force_update: bool,
+    diff_mode: Option<DiffMode>,
+    changes: Vec<Vec<(usize, usize)>>,
+    baseline: Vec<String>,
+    dirty: Vec<Vec<bool>>,
+    input_mode: Option<InputMode>,
+    input: String,
+    pending_command: Option<String>,
+    search: Option<String>,
+    search_origin: u16,
+    matches: Vec<usize>,
+    current_match: usize,
+}
+
+impl DisplayState {
+    fn new() -> Self {
+        Self {
+            scroll_y: 0,
+            scroll_x: 0,
+            content: Vec::new(),
+            header: Vec::new(),
+            header_lines: 0,
+            freeze_cols: 0,
+            max_width: 0,
+            max_frozen: 0,
+            row_starts: RefCell::new(HashMap::new()),
+            line_numbers: Vec::new(),
+            source_content: Vec::new(),
+            source_streams: Vec::new(),
+            hide_stderr: false,
+            previous_source: Vec::new(),
+            changes_only: false,
+            filters: Vec::new(),
+            sort: None,
+            color_rules: Vec::new(),
+            highlight: None,
+            message: None,
+            snapshot_ansi: false,
+            last_update: Instant::now(),
+            read_started: Instant::now(),
+            precise: false,
+            drift: Duration::ZERO,
+            overlap: OverlapPolicy::Skip,
+            skipped: 0,
+            started: Instant::now(),
+            refresh_count: 0,
+            computed: None,
+            stable_count: 0,
+            bell_at: None,
+            command_status: None,
+            failed: None,
+            stale: false,
+            show_status: true,
+            reading: false,
+            last_error: None,
+            file_missing: false,
+            has_good_content: false,
+            last_render: Instant::now(),
+            paused: false,
+            follow: false,
+            follow_disengaged: false,
+            show_line_numbers: false,
+            show_help: false,
+            confirming_quit: false,
+            help_scroll: 0,
+            scrollbar: false,
+            wrap: false,
+            force_update: false,
+            diff_mode: None,
+            changes: Vec::new(),
+            baseline: Vec::new(),
+            dirty: Vec::new(),
+            input_mode: None,
+            input: String::new(),
+            pending_command: None,
+            search: None,
+            search_origin: 0,
+            matches: Vec::new(),
+            current_match: 0,
+        }
+    }
+
+    fn gutter_width(&self) -> u16 {
+        if self.show_line_numbers {
+            let largest = self.line_numbers.last().map_or(self.content.len(), |&n| n + 1);
+            largest.max(1).to_string().len() as u16 + 1
+        } else {
+            0
+        }
+    }
+
+    fn text_width(&self, width: u16) -> usize {
+        (width.saturating_sub(self.gutter_width()) as usize).max(1)
+    }
+
+    fn line_rows(&self, line: &str, width: u16) -> usize {
+        if self.wrap {
+            wrap_row_count(line, self.text_width(width))
+        } else {
+            1
+        }
+    }
+
+    fn with_row_starts<R>(&self, width: u16, f: impl FnOnce(&[usize]) -> R) -> R {
+        let text_width = self.text_width(width);
+        let mut cache = self.row_starts.borrow_mut();
+        if !cache.contains_key(&text_width) {
+            if cache.len() >= ROW_CACHE_WIDTHS {
+                cache.clear();
+            }
+            let mut starts = Vec::with_capacity(self.content.len() + 1);
+            let mut total = 0;
+            starts.push(total);
+            for line in &self.content {
+                total += self.line_rows(line, width);
+                starts.push(total);
+            }
+            cache.insert(text_width, starts);
+        }
+        f(&cache[&text_width])
+    }
+
+    fn row_offset(&self, line_index: usize, width: u16) -> usize {
+        if !self.wrap {
+            return line_index;
+        }
+        self.with_row_starts(width, |starts| starts[line_index.min(starts.len() - 1)])
+    }
+
+    fn line_at_row(&self, row: usize, width: u16) -> (usize, usize) {
+        if !self.wrap {
+            return (row, 0);
+        }
+        self.with_row_starts(width, |starts| {
+            let index = starts.partition_point(|&start| start <= row) - 1;
+            if index >= self.content.len() {
+                (self.content.len(), 0)
+            } else {
+                (index, row - starts[index])
+            }
+        })
+    }
+
+    fn view_size(&self, width: u16, height: u16) -> (u16, u16) {
+        if !self.scrollbar {
+            return (width, height);
+        }
+        let (_, max_scroll_y) = self.max_scroll(width, height);
+        let width = if max_scroll_y > 0 { width.saturating_sub(1) } else { width };
+        let (max_scroll_x, _) = self.max_scroll(width, height);
+        if max_scroll_x == 0 {
+            return (width, height);
+        }
+        let height = height.saturating_sub(1);
+        if max_scroll_y == 0 && self.max_scroll(width, height).1 > 0 {
+            (width.saturating_sub(1), height)
+        } else {
+            (width, height)
+        }
+    }
+
+    fn status_visible(&self) -> bool {
+        self.show_status || self.input_mode.is_some() || self.confirming_quit || self.failed.is_some()
+    }
+
+    fn status_height(&self) -> u16 {
+        self.status_visible() as u16 + self.last_error.is_some() as u16
+    }
+
+    fn body_height(&self, height: u16) -> u16 {
+        height.saturating_sub(self.header.len() as u16)
+    }
+
+    fn max_scroll(&self, width: u16, height: u16) -> (u16, u16) {
+        let total_rows = self.row_offset(self.content.len(), width);
+        let max_scroll_y = total_rows.saturating_sub(self.body_height(height) as usize) as u16;
+        if self.wrap {
+            return (0, max_scroll_y);
+        }
+        let frozen = self.frozen_width();
+        let scroll_width = if frozen > 0 {
+            self.text_width(width).saturating_sub(frozen + 1)
+        } else {
+            self.text_width(width)
+        };
+        let max_scroll_x = self.max_width
+            .saturating_sub(frozen)
+            .saturating_sub(scroll_width);
+        (max_scroll_x.min(u16::MAX as usize) as u16, max_scroll_y)
+    }
+
+    fn frozen_width(&self) -> usize {
+        if self.wrap {
+            0
+        } else {
+            self.max_frozen
+        }
+    }
+
+    fn measure(&mut self) {
+        let lines = || self.header.iter().chain(&self.content);
+        self.max_width = lines().map(|line| visual_width(line)).max().unwrap_or(0);
+        self.max_frozen = if self.freeze_cols == 0 {
+            0
+        } else {
+            lines().map(|line| field_end_column(line, self.freeze_cols)).max().unwrap_or(0)
+        };
+        self.row_starts.get_mut().clear();
+    }
+
+    fn scroll_line(&self, line: Line<'static>, frozen: usize) -> Line<'static> {
+        if frozen == 0 {
+            return crop_line_for_scroll(line, self.scroll_x);
+        }
+        let mut row = truncate_line(line.clone(), frozen);
+        let scrolled = crop_line_for_scroll(line, (frozen + self.scroll_x as usize).min(u16::MAX as usize) as u16);
+        row.spans.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
+        row.spans.extend(scrolled.spans);
+        row
+    }
+
+    fn clamp_scroll(&mut self, width: u16, height: u16) {
+        let (max_scroll_x, max_scroll_y) = self.max_scroll(width, height);
+        self.scroll_y = if self.follow {
+            max_scroll_y
+        } else {
+            self.scroll_y.min(max_scroll_y)
+        };
+        self.scroll_x = self.scroll_x.min(max_scroll_x);
+    }
+
+    fn update_content(&mut self, new_content: Vec<String>, streams: Vec<Stream>, width: u16, height: u16) -> bool {
+        let changed = new_content != self.source_content || streams != self.source_streams;
+        if !changed && !self.changes_only {
+            if self.diff_mode == Some(DiffMode::Changes) {
+                self.changes.clear();
+            }
+            return false;
+        }
+
+        let anchor = if self.follow || self.scroll_y == 0 {
+            None
+        } else {
+            let (line, offset) = self.line_at_row(self.scroll_y as usize, width);
+            self.content.get(line).map(|text| (line, offset, text.clone()))
+        };
+        self.previous_source = std::mem::replace(&mut self.source_content, new_content);
+        self.source_streams = streams;
+        self.header = self.source_content.iter().take(self.header_lines).cloned().collect();
+        let (view, line_numbers) = self.filtered_content();
+
+        match self.diff_mode {
+            Some(DiffMode::Changes) => {
+                self.changes = diff_lines(&self.content, &view);
+            }
+            Some(DiffMode::Permanent) => {
+                mark_dirty(&mut self.dirty, &diff_lines(&self.baseline, &view));
+                self.changes = dirty_ranges(&self.dirty);
+            }
+            None => {}
+        }
+
+        self.content = view;
+        self.line_numbers = line_numbers;
+        self.measure();
+        if let Some((line, offset, text)) = anchor {
+            let found = (0..self.content.len())
+                .filter(|&index| self.content[index] == text)
+                .min_by_key(|&index| index.abs_diff(line));
+            if let Some(index) = found {
+                self.scroll_y = (self.row_offset(index, width) + offset).min(u16::MAX as usize) as u16;
+            }
+        }
+        self.find_matches();
+        self.clamp_scroll(width, height);
+        changed
+    }
+    
+    fn filtered_content(&self) -> (Vec<String>, Vec<usize>) {
+        let mut view = self.source_content
+            .iter()
+            .enumerate()
+            .skip(self.header_lines)
+            .filter(|(i, _)| !self.hide_stderr || self.source_streams.get(*i) != Some(&Stream::Stderr))
+            .filter(|(i, line)| !self.changes_only || self.previous_source.get(*i) != Some(line))
+            .filter(|(_, line)| {
+                if self.filters.is_empty() {
+                    return true;
+                }
+                let plain = strip_ansi(line);
+                self.filters.iter().all(|filter| filter.keeps(&plain))
+            })
+            .map(|(i, line)| (line.clone(), i))
+            .collect::<Vec<_>>();
+        if let Some(sort) = self.sort {
+            view.sort_by(|(a, _), (b, _)| sort.compare(a, b));
+        }
+        view.into_iter().unzip()
+    }
+    
+    fn hidden_stderr(&self) -> usize {
+        if !self.hide_stderr {
+            return 0;
+        }
+        self.source_streams
+            .iter()
+            .skip(self.header_lines)
+            .filter(|&&stream| stream == Stream::Stderr)
+            .count()
+    }
+    
+    fn refilter(&mut self, width: u16, height: u16) {
+        self.header = self.source_content.iter().take(self.header_lines).cloned().collect();
+        (self.content, self.line_numbers) = self.filtered_content();
+        self.measure();
+        self.changes.clear();
+        self.reset_baseline();
+        self.find_matches();
+        self.clamp_scroll(width, height);
+    }
+    
+    fn set_filter(&mut self, pattern: &str, width: u16, height: u16) {
+        if pattern.is_empty() {
+            self.filters.clear();
+        } else {
+            match LineFilter::parse(pattern) {
+                Ok(filter) => self.filters.push(filter),
+                Err(e) => {
+                    self.message = Some((format!("无效的过滤表达式: {}", e), Color::Red));
+                    return;
+                }
+            }
+        }
+        self.refilter(width, height);
+    }
+    
+    fn find_matches(&mut self) {
+        self.matches = match &self.search {
+            Some(pattern) => self.content
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| strip_ansi(line).contains(pattern.as_str()))
+                .map(|(i, _)| i)
+                .collect(),
+            None => Vec::new(),
+        };
+        self.current_match = self.current_match.min(self.matches.len().saturating_sub(1));
+    }
+    
+    fn start_search(&mut self, pattern: String, width: u16, height: u16) {
+        if pattern.is_empty() {
+            self.clear_search();
+            return;
+        }
+        
+        self.search = Some(pattern);
+        self.find_matches();
+        let (top_line, _) = self.line_at_row(self.scroll_y as usize, width);
+        let first = self.matches
+            .iter()
+            .position(|&line| line >= top_line)
+            .unwrap_or(0);
+        self.jump_to_match(first, width, height);
+    }
+    
+    fn clear_search(&mut self) {
+        self.search = None;
+        self.matches.clear();
+        self.current_match = 0;
+    }
+    
+    fn jump_to_match(&mut self, index: usize, width: u16, height: u16) {
+        if let Some(&line) = self.matches.get(index) {
+            let (_, max_scroll_y) = self.max_scroll(width, height);
+            self.current_match = index;
+            self.scroll_y = (self.row_offset(line, width) as u16).min(max_scroll_y);
+            self.disengage_follow();
+        }
+    }
+    
+    fn handle_input_key(&mut self, key_event: &KeyEvent, width: u16, height: u16) -> bool {
+        match key_event.code {
+            KeyCode::Esc => {
+                if self.input_mode.take() == Some(InputMode::Search) {
+                    self.clear_search();
+                    self.scroll_y = self.search_origin;
+                }
+                self.input.clear();
+            }
+            KeyCode::Enter => {
+                let input = std::mem::take(&mut self.input);
+                match self.input_mode.take() {
+                    Some(InputMode::Search) if self.search.as_deref() != Some(input.as_str()) => {
+                        self.start_search(input, width, height)
+                    }
+                    Some(InputMode::Search) => {}
+                    Some(InputMode::Filter) => self.set_filter(&input, width, height),
+                    Some(InputMode::Goto) => self.goto_line(&input, width, height),
+                    Some(InputMode::Command) if !input.trim().is_empty() => self.pending_command = Some(input),
+                    Some(InputMode::Command) | None => {}
+                }
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.preview_search(width, height);
+            }
+            KeyCode::Char(c) if self.input_mode == Some(InputMode::Goto) && !(c.is_ascii_digit() || c == '$') => {}
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                self.preview_search(width, height);
+            }
+            _ => return false,
+        }
+        true
+    }
+    
+    fn goto_line(&mut self, input: &str, width: u16, height: u16) {
+        let (_, max_scroll_y) = self.max_scroll(width, height);
+        if input == "$" {
+            self.scroll_y = max_scroll_y;
+            return;
+        }
+        if input.is_empty() || !input.chars().all(|c| c.is_ascii_digit()) {
+            return;
+        }
+        let number = input.parse::<usize>().unwrap_or(usize::MAX);
+        let index = self.line_numbers.partition_point(|&line| line + 1 < number);
+        self.scroll_y = (self.row_offset(index, width).min(u16::MAX as usize) as u16).min(max_scroll_y);
+        self.disengage_follow();
+    }
+    
+    fn preview_search(&mut self, width: u16, height: u16) {
+        if self.input_mode != Some(InputMode::Search) {
+            return;
+        }
+        self.scroll_y = self.search_origin;
+        self.start_search(self.input.clone(), width, height);
+    }
+    
+    fn reset_baseline(&mut self) {
+        self.baseline = self.content.clone();
+        self.dirty.clear();
+        if self.diff_mode == Some(DiffMode::Permanent) {
+            self.changes.clear();
+        }
+    }
+    
+    fn styled_line(&self, index: usize) -> Line<'static> {
+        let line = &self.content[index];
+        let mut styled_line = parse_ansi_line(line);
+        if !self.color_rules.is_empty() {
+            let plain = strip_ansi(line);
+            for rule in &self.color_rules {
+                styled_line = patch_line_style(
+                    styled_line,
+                    &regex_ranges(&plain, &rule.regex),
+                    Style::default().fg(rule.color),
+                );
+            }
+        }
+        if let Some(highlight) = &self.highlight {
+            styled_line = patch_line_style(
+                styled_line,
+                &regex_ranges(&strip_ansi(line), highlight),
+                Style::default().fg(Color::Black).bg(Color::LightMagenta),
+            );
+        }
+        if let Some(changes) = self.changes.get(index) {
+            styled_line = patch_line_style(
+                styled_line,
+                changes,
+                Style::default().add_modifier(Modifier::REVERSED),
+            );
+        }
+        if let Some(pattern) = &self.search {
+            styled_line = patch_line_style(
+                styled_line,
+                &find_ranges(&strip_ansi(line), pattern),
+                Style::default().fg(Color::Black).bg(Color::Yellow),
+            );
+        }
+        styled_line
+    }
+    
+    fn gutter_span(&self, number: Option<usize>) -> Span<'static> {
+        let gutter_width = self.gutter_width() as usize;
+        let gutter = match number {
+            Some(number) => format!("{:>width$} ", number, width = gutter_width - 1),
+            None => " ".repeat(gutter_width),
+        };
+        Span::styled(gutter, Style::default().fg(Color::DarkGray).add_modifier(Modifier::DIM))
+    }
+    
+    fn get_display_text(&self, width: u16, height: u16) -> Text<'static> {
+        let frozen = self.frozen_width();
+        let mut lines: Vec<Line<'static>> = self.header
+            .iter()
+            .take(height as usize)
+            .enumerate()
+            .map(|(index, line)| {
+                let mut row = if self.wrap {
+                    parse_ansi_line(line)
+                } else {
+                    self.scroll_line(parse_ansi_line(line), frozen)
+                };
+                if index + 1 == self.header.len() {
+                    row = row.patch_style(Style::default().add_modifier(Modifier::UNDERLINED));
+                }
+                if self.show_line_numbers {
+                    row.spans.insert(0, self.gutter_span(Some(index + 1)));
+                }
+                row
+            })
+            .collect();
+        
+        if self.content.is_empty() && self.changes_only {
+            lines.push(Line::from("没有变化"));
+            return Text::from(lines);
+        }
+        
+        if self.content.is_empty() && !self.filters.is_empty() {
+            lines.push(Line::from("没有匹配过滤条件的行"));
+            return Text::from(lines);
+        }
+        
+        let (start_line, skip_rows) = self.line_at_row(self.scroll_y as usize, width);
+        if start_line >= self.content.len() || height == 0 {
+            lines.push(Line::from("没有内容可显示"));
+            return Text::from(lines);
+        }
+        
+        for index in start_line..self.content.len() {
+            if lines.len() >= height as usize {
+                break;
+            }
+            
+            let styled_line = self.styled_line(index);
+            let rows = if self.wrap {
+                wrap_line(styled_line, self.text_width(width))
+            } else {
+                vec![self.scroll_line(styled_line, frozen)]
+            };
+            
+            for (row_index, mut row) in rows.into_iter().enumerate() {
+                if index == start_line && row_index < skip_rows {
+                    continue;
+                }
+                if self.show_line_numbers {
+                    let number = if row_index == 0 {
+                        Some(self.line_numbers.get(index).copied().unwrap_or(index) + 1)
+                    } else {
+                        None
+                    };
+                    row.spans.insert(0, self.gutter_span(number));
+                }
+                lines.push(row);
+                if lines.len() >= height as usize {
+                    break;
+                }
+            }
+        }
+        
+        Text::from(lines)
+    }
+
+    fn handle_key_event(
+        &mut self,
+        key_event: &KeyEvent,
+        width: u16,
+        height: u16,
+    ) -> bool {
+        if key_event.kind != KeyEventKind::Press {
+            return false;
+        }
+        
+        self.message = None;
+        
+        if self.show_help {
+            let max_help_scroll = help_line_count().saturating_sub(height.saturating_sub(1));
+            match key_event.code {
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.help_scroll = (self.help_scroll + 1).min(max_help_scroll);
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.help_scroll = self.help_scroll.saturating_sub(1);
+                }
+                _ => self.show_help = false,
+            }
+            return true;
+        }
+        
+        if self.input_mode.is_some() {
+            return self.handle_input_key(key_event, width, height);
+        }
+        
+        let (max_scroll_x, max_scroll_y) = self.max_scroll(width, height);
+        let page = self.body_height(height).max(1);
+        let half_page = (page / 2).max(1);
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll_by(0, -1, width, height);
+                true
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll_by(0, 1, width, height);
+                true
+            }
+            
+            KeyCode::PageUp => {
+                self.scroll_by(0, -(page as i32), width, height);
+                true
+            }
+            KeyCode::PageDown => {
+                self.scroll_by(0, page as i32, width, height);
+                true
+            }
+            KeyCode::Char('u') if ctrl => {
+                self.scroll_by(0, -(half_page as i32), width, height);
+                true
+            }
+            KeyCode::Char('d') if ctrl => {
+                self.scroll_by(0, half_page as i32, width, height);
+                true
+            }
+            
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.scroll_by(-1, 0, width, height);
+                true
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.scroll_by(1, 0, width, height);
+                true
+            }
+            
+            KeyCode::Home if ctrl => {
+                self.scroll_y = 0;
+                self.disengage_follow();
+                true
+            }
+            KeyCode::Char('g') => {
+                self.scroll_y = 0;
+                self.disengage_follow();
+                true
+            }
+            KeyCode::End if ctrl => {
+                self.scroll_y = max_scroll_y;
+                self.set_follow(true);
+                true
+            }
+            KeyCode::Char('G') => {
+                self.scroll_y = max_scroll_y;
+                self.set_follow(true);
+                true
+            }
+            
+            KeyCode::Home => {
+                self.scroll_x = 0;
+                true
+            }
+            KeyCode::End => {
+                self.scroll_x = max_scroll_x;
+                true
+            }
+            
+            KeyCode::Char('F') => {
+                self.set_follow(!self.follow);
+                self.clamp_scroll(width, height);
+                true
+            }
+            
+            KeyCode::Char(' ') | KeyCode::Char('p') => {
+                self.toggle_pause();
+                true
+            }
+            
+            KeyCode::Char('w') => {
+                let (top_line, _) = self.line_at_row(self.scroll_y as usize, width);
+                self.wrap = !self.wrap;
+                self.scroll_y = self.row_offset(top_line, width).min(u16::MAX as usize) as u16;
+                self.clamp_scroll(width, height);
+                true
+            }
+            KeyCode::Char('e') => {
+                self.hide_stderr = !self.hide_stderr;
+                self.refilter(width, height);
+                true
+            }
+            KeyCode::Char('#') => {
+                self.show_line_numbers = !self.show_line_numbers;
+                self.clamp_scroll(width, height);
+                true
+            }
+            KeyCode::Char('/') => {
+                self.input_mode = Some(InputMode::Search);
+                self.input.clear();
+                self.search_origin = self.scroll_y;
+                true
+            }
+            KeyCode::Char('&') => {
+                self.input_mode = Some(InputMode::Filter);
+                self.input.clear();
+                true
+            }
+            KeyCode::Char('s') => {
+                self.save_snapshot();
+                true
+            }
+            KeyCode::Char('?') | KeyCode::F(1) => {
+                self.show_help = true;
+                self.help_scroll = 0;
+                true
+            }
+            KeyCode::Char(':') => {
+                self.input_mode = Some(InputMode::Goto);
+                self.input.clear();
+                true
+            }
+            KeyCode::Char('!') => {
+                self.input_mode = Some(InputMode::Command);
+                self.input.clear();
+                true
+            }
+            KeyCode::Char('n') if !self.matches.is_empty() => {
+                let next = (self.current_match + 1) % self.matches.len();
+                self.jump_to_match(next, width, height);
+                true
+            }
+            KeyCode::Char('N') if !self.matches.is_empty() => {
+                let prev = (self.current_match + self.matches.len() - 1) % self.matches.len();
+                self.jump_to_match(prev, width, height);
+                true
+            }
+            KeyCode::Esc if self.search.is_some() => {
+                self.clear_search();
+                true
+            }
+            
+            KeyCode::Char('b') if self.diff_mode == Some(DiffMode::Permanent) => {
+                self.reset_baseline();
+                true
+            }
+            
+            _ => false,
+        }
+    }
+    
+    fn handle_mouse_event(&mut self, mouse_event: &MouseEvent, width: u16, height: u16) -> bool {
+        if self.show_help {
+            return false;
+        }
+        let horizontal = mouse_event.modifiers.contains(KeyModifiers::SHIFT);
+        let (dx, dy) = match mouse_event.kind {
+            MouseEventKind::ScrollUp if horizontal => (-MOUSE_SCROLL_STEP, 0),
+            MouseEventKind::ScrollDown if horizontal => (MOUSE_SCROLL_STEP, 0),
+            MouseEventKind::ScrollUp => (0, -MOUSE_SCROLL_STEP),
+            MouseEventKind::ScrollDown => (0, MOUSE_SCROLL_STEP),
+            MouseEventKind::ScrollLeft => (-MOUSE_SCROLL_STEP, 0),
+            MouseEventKind::ScrollRight => (MOUSE_SCROLL_STEP, 0),
+            _ => return false,
+        };
+        self.scroll_by(dx, dy, width, height);
+        true
+    }
+    
+    fn scroll_by(&mut self, dx: i32, dy: i32, width: u16, height: u16) {
+        let (max_scroll_x, max_scroll_y) = self.max_scroll(width, height);
+        if dy < 0 {
+            self.scroll_y = self.scroll_y.saturating_sub(dy.unsigned_abs() as u16);
+            self.disengage_follow();
+        } else {
+            self.scroll_y = self.scroll_y.saturating_add(dy as u16).min(max_scroll_y);
+        }
+        if dx < 0 {
+            self.scroll_x = self.scroll_x.saturating_sub(dx.unsigned_abs() as u16);
+        } else {
+            self.scroll_x = self.scroll_x.saturating_add(dx as u16).min(max_scroll_x);
+        }
+    }
+    
+    fn save_snapshot(&mut self) {
+        let epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let path = format!("grain-snapshot-{}.txt", epoch);
+        let text: String = self.header
+            .iter()
+            .chain(&self.content)
+            .map(|line| {
+                let line = if self.snapshot_ansi { line.clone() } else { strip_ansi(line) };
+                line + "\n"
+            })
+            .collect();
+        self.message = Some(match std::fs::write(&path, text) {
+            Ok(()) => (format!("已保存快照: {}", path), Color::Green),
+            Err(e) => (format!("保存快照失败: {}", e), Color::Red),
+        });
+    }
+    
+    fn set_follow(&mut self, follow: bool) {
+        self.follow = follow;
+        self.follow_disengaged = false;
+    }
+    
+    fn disengage_follow(&mut self) {
+        if self.follow {
+            self.follow = false;
+            self.follow_disengaged = true;
+        }
+    }
+    
+    fn mark_rendered(&mut self) {
+        self.last_render = Instant::now();
+    }
+
+    fn should_update(&self, now: Instant, interval: Duration) -> bool {
+        if self.paused || self.failed.is_some() {
+            return false;
+        }
+        self.force_update || now.duration_since(self.last_update) >= interval
+    }
+    
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        if !self.paused {
+            self.force_update = true;
+        }
+    }
+    
+    fn mark_updated(&mut self, interval: Duration) {
+        let overrun = self.read_started.elapsed().as_nanos() / interval.as_nanos().max(1);
+        let missed = overrun.min(u32::MAX as u128) as u32;
+        if self.overlap == OverlapPolicy::Skip {
+            self.skipped += missed as u64;
+        }
+        self.last_update = if missed > 0 && self.overlap == OverlapPolicy::Wait {
+            self.read_started
+        } else if self.precise {
+            let elapsed = Instant::now().duration_since(self.started);
+            let slots = elapsed.as_nanos() / interval.as_nanos().max(1);
+            self.started + interval * slots.min(u32::MAX as u128) as u32
+        } else {
+            Instant::now()
+        };
+        self.force_update = false;
+        self.refresh_count += 1;
+    }
+}
+
+fn format_interval(interval: Duration) -> String {
+    let ms = interval.as_millis();
+    if ms < 1000 {
+        return format!("{}ms", ms);
+    }
+    let mut text = String::new();
+    let hours = ms / 3_600_000;
+    let minutes = ms % 3_600_000 / 60_000;
+    let rest = ms % 60_000;
+    if hours > 0 {
+        text.push_str(&format!("{}h", hours));
+    }
+    if minutes > 0 {
+        text.push_str(&format!("{}m", minutes));
+    }
+    if rest > 0 {
+        text.push_str(&format!("{}s", rest as f64 / 1000.0));
+    }
+    text
+}
+
+fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}h{:02}m{:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m{:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+fn source_label(config: &AppConfig) -> String {
+    if let Some(cmd) = &config.command {
+        cmd.clone()
+    } else if let Some(url) = &config.url {
+        url.clone()
+    } else if let Some(file) = &config.file {
         file.as_str().to_string()
+    } else if config.stdin.is_some() {
+        "标准输入".to_string()
     } else {
         "/proc/interrupts".to_string()
+    }
+}
+
+fn get_status_line(config: &AppConfig, state: &DisplayState, width: u16, _height: u16) -> Line<'static> {
+    if let Some(code) = state.failed {
+        return Line::from(Span::styled(
+            format!("命令执行失败 (exit {}) — 按任意键退出", code),
+            Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    if state.confirming_quit {
+        return Line::from(Span::styled(
+            "退出? (y/n)",
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    if let Some(mode) = state.input_mode {
+        let prompt = match mode {
+            InputMode::Search => "/",
+            InputMode::Filter => "&",
+            InputMode::Goto => ":",
+            InputMode::Command => "!",
+        };
+        return Line::from(vec![
+            Span::raw(format!("{}{}", prompt, state.input)),
+            Span::styled(" ", Style::default().add_modifier(Modifier::REVERSED)),
+        ]);
+    }
+
+    if let Some((message, color)) = &state.message {
+        return Line::from(Span::styled(message.clone(), Style::default().fg(*color)));
+    }
+
+    let source = match &config.chdir {
+        Some(dir) if config.command.is_some() => format!("{}: {}", dir, source_label(config)),
+        _ => source_label(config),
+    };
+    let max_len = (width as usize).saturating_sub(10);
+    let source = if config.command.is_some() && visual_width(&source) > max_len {
+        format!("{}...", truncate_to_width(&source, max_len))
+    } else {
+        source
     };
 
-    let status_text = format!("{}  {}", source, format_interval(config.interval));
-    let green_span = Span::styled(
-        status_text,
-        Style::default().fg(Color::Green)
-    );
-    Line::from(green_span)
+    let mut status_text = match &config.status_format {
+        Some(template) => template
+            .replace("{source}", &source)
+            .replace("{interval}", &format_interval(config.interval))
+            .replace("{time}", &Local::now().format("%H:%M:%S").to_string())
+            .replace("{count}", &state.refresh_count.to_string())
+            .replace("{uptime}", &format_uptime(state.started.elapsed())),
+        None => format!(
+            "{}  {}  #{}  up {}",
+            source,
+            format_interval(config.interval),
+            state.refresh_count,
+            format_uptime(state.started.elapsed())
+        ),
+    };
+    if state.follow {
+        status_text.push_str("  [FOLLOW]");
+    } else if state.follow_disengaged {
+        status_text.push_str("  [FOLLOW OFF]");
+    }
+    if !state.filters.is_empty() {
+        let labels: Vec<String> = state.filters.iter().map(LineFilter::label).collect();
+        status_text.push_str(&format!(
+            "  filter: {}  {}/{}",
+            labels.join(" "),
+            state.content.len(),
+            state.source_content.len()
+        ));
+    }
+    if state.search.is_some() {
+        let current = if state.matches.is_empty() { 0 } else { state.current_match + 1 };
+        status_text.push_str(&format!("  {}/{} matches", current, state.matches.len()));
+    }
+    if state.precise {
+        status_text.push_str(&format!("  drift {}", format_elapsed(state.drift)));
+    }
+    if let Some((compression, size)) = *config.decompressed.lock().unwrap() {
+        status_text.push_str(&format!("  {} {}", compression.label(), format_size(size)));
+    }
+    if state.skipped > 0 {
+        status_text.push_str(&format!("  skipped {}", state.skipped));
+    }
+    let hidden = state.hidden_stderr();
+    if hidden > 0 {
+        status_text.push_str(&format!("  stderr hidden {}", hidden));
+    }
+    if let Some(cycles) = config.equexit {
+        status_text.push_str(&format!("  stable {}/{}", state.stable_count, cycles));
+    }
+    let countdown = if state.paused {
+        "paused".to_string()
+    } else if state.reading && state.read_started.elapsed() >= SPINNER_DELAY {
+        let frame = state.read_started.elapsed().as_millis() / 100 % SPINNER.len() as u128;
+        format!("{} running", SPINNER[frame as usize])
+    } else {
+        let remaining = config.interval.saturating_sub(state.last_update.elapsed());
+        format!("next: {:.1}s", remaining.as_secs_f64())
+    };
+    let mut right = Vec::new();
+    if let Some(status) = state.command_status {
+        let (text, color) = match status.exit {
+            CommandExit::Code(0) => ("exit 0".to_string(), Color::Green),
+            CommandExit::Code(code) => (format!("exit {}", code), Color::Red),
+            CommandExit::Killed => ("killed".to_string(), Color::Red),
+            CommandExit::Timeout => ("timeout".to_string(), Color::Red),
+        };
+        let text = if state.stale { format!("{} [stale]", text) } else { text };
+        right.push((2, text, color));
+        right.push((0, format!("took {}", format_elapsed(status.duration)), config.status_color));
+    }
+    if state.file_missing {
+        right.push((2, "file missing".to_string(), Color::Yellow));
+    }
+    right.push((1, countdown, config.status_color));
+    match &state.computed {
+        Some(Ok(value)) => right.push((2, format!("= {}", format_number(*value)), config.status_color)),
+        Some(Err(e)) => right.push((2, format!("= ? {}", e), Color::Yellow)),
+        None => {}
+    }
+    if config.clock {
+        right.push((1, Local::now().format("%H:%M:%S").to_string(), config.status_color));
+    }
+
+    let right_width = |right: &[(u8, String, Color)]| {
+        right.iter().map(|(_, text, _)| visual_width(text) + 2).sum::<usize>()
+    };
+    let reserved = visual_width(&status_text).min(MIN_STATUS_WIDTH);
+    while !right.is_empty() && reserved + right_width(&right) > width as usize {
+        let lowest = (0..right.len()).min_by_key(|&i| right[i].0).unwrap();
+        right.remove(lowest);
+    }
+
+    let available = (width as usize).saturating_sub(right_width(&right));
+    if visual_width(&status_text) > available {
+        status_text = truncate_to_width(&status_text, available);
+    }
+    let padding = (width as usize).saturating_sub(visual_width(&status_text) + right_width(&right));
+    let style = Style::default().fg(config.status_color);
+    let mut spans = vec![Span::styled(format!("{}{}", status_text, " ".repeat(padding)), style)];
+    for (_, text, color) in right {
+        spans.push(Span::styled("  ", style));
+        spans.push(Span::styled(text, Style::default().fg(color)));
+    }
+    let line = Line::from(spans);
+    if state.bell_at.is_some_and(|at| at.elapsed() < BELL_FLASH) {
+        line.patch_style(Style::default().add_modifier(Modifier::REVERSED))
+    } else {
+        line
+    }
 }
 
-fn render_ui(frame: &mut Frame, config: &AppConfig, state: &DisplayState) {
-    let full_area = frame.size();
-    
-    frame.render_widget(Clear, full_area);
-    
-    const STATUS_HEIGHT: u16 = 1;
+fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let (number, unit) = match text.char_indices().find(|(_, c)| c.is_ascii_alphabetic()) {
+        Some((index, _)) => text.split_at(index),
+        None => (text, ""),
+    };
+    let multiplier: u64 = match unit.to_ascii_uppercase().trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        _ => return Err(format!("未知的单位: {}", unit)),
+    };
+    let number = number.parse::<f64>().ok().filter(|number| *number > 0.0).ok_or_else(|| format!("无效的大小: {}", text))?;
+    Ok((number * multiplier as f64) as u64)
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        bytes if bytes >= 1 << 30 => format!("{:.1}G", bytes as f64 / (1u64 << 30) as f64),
+        bytes if bytes >= 1 << 20 => format!("{:.1}M", bytes as f64 / (1u64 << 20) as f64),
+        bytes if bytes >= 1 << 10 => format!("{:.1}K", bytes as f64 / (1u64 << 10) as f64),
+        bytes => format!("{}B", bytes),
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let ms = elapsed.as_millis();
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    }
+}
+
+fn render_error_pane(frame: &mut Frame, config: &AppConfig, errors: &DisplayState, area: Rect, focused: bool) {
+    let style = if focused {
+        Style::default().fg(Color::Black).bg(Color::Red)
+    } else {
+        Style::default().fg(Color::Red).add_modifier(Modifier::DIM)
+    };
+    let title = Line::from(Span::styled(format!(" stderr  {} ", errors.source_content.len()), style));
+    frame.render_widget(Paragraph::new(title), Rect { height: 1, ..area });
+    if area.height > 1 {
+        let area = Rect { y: area.y + 1, height: area.height - 1, ..area };
+        render_ui(frame, config, errors, area, focused);
+    }
+}
 
-    let status_area = if full_area.height >= STATUS_HEIGHT {
+fn render_ui(frame: &mut Frame, config: &AppConfig, state: &DisplayState, full_area: Rect, focused: bool) {
+    let status_height = state.status_height();
+
+    let status_area = if state.status_visible() && full_area.height >= 1 {
         Some(Rect {
-            x: 0,
-            y: 0,
-            width: full_area.width,
-            height: STATUS_HEIGHT,
+            height: 1,
+            ..full_area
         })
     } else {
         None
     };
 
-    let (content_y, content_height) = if full_area.height >= STATUS_HEIGHT + 1 {
-        (STATUS_HEIGHT, full_area.height - STATUS_HEIGHT)
+    let (content_y, content_height) = if full_area.height > status_height {
+        (full_area.y + status_height, full_area.height - status_height)
     } else {
-        (0, 1)
+        (full_area.y, 1)
     };
     
     let content_area = Rect {
-        x: 0,
+        x: full_area.x,
         y: content_y,
         width: full_area.width,
         height: content_height,
@@ -531,15 +3436,120 @@ fn render_ui(frame: &mut Frame, config: &AppConfig, state: &DisplayState) {
 
     if let Some(area) = status_area {
         let status_line = get_status_line(config, state, content_area.width, content_area.height);
+        let status_line = if focused {
+            status_line
+        } else {
+            status_line.patch_style(Style::default().add_modifier(Modifier::DIM))
+        };
         frame.render_widget(Paragraph::new(status_line), area);
     }
 
-    let display_text = state.get_display_text(content_area.width, content_area.height);
+    if let Some(error) = &state.last_error {
+        if full_area.height > status_height {
+            let banner = Line::from(Span::styled(
+                format!("读取失败: {}", error),
+                Style::default().fg(Color::White).bg(Color::Red),
+            ));
+            frame.render_widget(Paragraph::new(banner), Rect { y: full_area.y + state.status_visible() as u16, height: 1, ..full_area });
+        }
+    }
+
+    let (text_width, text_height) = state.view_size(content_area.width, content_area.height);
+    let text_area = Rect {
+        width: text_width,
+        height: text_height,
+        ..content_area
+    };
+    let display_text = state.get_display_text(text_area.width, text_area.height);
     let paragraph = Paragraph::new(display_text);
-    frame.render_widget(paragraph, content_area);
+    frame.render_widget(paragraph, text_area);
+
+    if state.scrollbar {
+        render_scrollbars(frame, state, text_area);
+    }
 }
 
-fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<io::Stdout>>> {
+fn help_line_count() -> u16 {
+    KEY_BINDINGS.len() as u16 + 1
+}
+
+fn render_help(frame: &mut Frame, config: &AppConfig, scroll: u16, area: Rect) {
+    let mut lines = vec![
+        Line::from(format!(
+            "来源: {}  间隔: {}  速度: {}x",
+            source_label(config),
+            format_interval(config.interval),
+            config.speed
+        )),
+    ];
+    lines.extend(
+        KEY_BINDINGS
+            .iter()
+            .map(|(keys, action)| Line::from(key_binding_line(keys, action))),
+    );
+
+    let content_width = lines.iter().map(Line::width).max().unwrap_or(0) as u16;
+    let width = (content_width + 4).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let max_scroll = help_line_count().saturating_sub(height.saturating_sub(2));
+    let title = if max_scroll > 0 { " 帮助 (j/k 滚动) " } else { " 帮助 " };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .padding(Padding::horizontal(1));
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines).block(block).scroll((scroll.min(max_scroll), 0)),
+        popup,
+    );
+}
+
+fn render_scrollbars(frame: &mut Frame, state: &DisplayState, text_area: Rect) {
+    let (max_scroll_x, max_scroll_y) = state.max_scroll(text_area.width, text_area.height);
+    let header_rows = (state.header.len() as u16).min(text_area.height);
+
+    if max_scroll_y > 0 {
+        let area = Rect {
+            x: text_area.x + text_area.width,
+            y: text_area.y + header_rows,
+            width: 1,
+            height: text_area.height - header_rows,
+        };
+        let mut scrollbar_state = ScrollbarState::new(max_scroll_y as usize + 1)
+            .position(state.scroll_y as usize)
+            .viewport_content_length(area.height as usize);
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None);
+        frame.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
+    }
+
+    if max_scroll_x > 0 {
+        let gutter = state.gutter_width().min(text_area.width);
+        let area = Rect {
+            x: text_area.x + gutter,
+            y: text_area.y + text_area.height,
+            width: text_area.width - gutter,
+            height: 1,
+        };
+        let mut scrollbar_state = ScrollbarState::new(max_scroll_x as usize + 1)
+            .position(state.scroll_x as usize)
+            .viewport_content_length(state.text_width(text_area.width));
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::HorizontalBottom)
+            .begin_symbol(None)
+            .end_symbol(None);
+        frame.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
+    }
+}
+
+fn setup_terminal(mouse: bool) -> io::Result<Terminal<CrosstermBackend<io::Stdout>>> {
     enable_raw_mode()?;
 
     let mut stdout = io::stdout();
@@ -549,6 +3559,9 @@ fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<io::Stdout>>> {
         EnterAlternateScreen,
         cursor::Hide
     )?;
+    if mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
     
     let backend = CrosstermBackend::new(stdout);
     Terminal::new(backend)
@@ -557,129 +3570,702 @@ fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<io::Stdout>>> {
 fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
     execute!(
         terminal.backend_mut(),
+        DisableMouseCapture,
         LeaveAlternateScreen,
         cursor::Show
     )?;
     
-    disable_raw_mode()?;
+    disable_raw_mode()?;
+    
+    Ok(())
+}
+
+fn add_panic() {
+    let orig_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |panic_info| {
+        let _ = disable_raw_mode();
+        let mut stdout = io::stdout();
+        let _ = execute!(
+            stdout,
+            DisableMouseCapture,
+            LeaveAlternateScreen,
+            cursor::Show
+        );
+        
+        orig_hook(panic_info);
+    }));
+}
+
+impl Pane {
+    fn new(config: AppConfig, area: Rect) -> io::Result<Self> {
+        let mut state = DisplayState::new();
+        state.diff_mode = config.differences;
+        state.follow = config.follow;
+        state.show_line_numbers = config.line_numbers;
+        state.wrap = config.wrap;
+        state.scrollbar = config.scrollbar;
+        state.header_lines = config.header;
+        state.freeze_cols = config.freeze_cols;
+        state.filters = config.filters.clone();
+        state.sort = config.sort;
+        state.color_rules = config.color_rules.clone();
+        state.highlight = config.highlight.clone();
+        state.changes_only = config.changes_only;
+        state.snapshot_ansi = config.snapshot_ansi;
+        state.precise = config.precise;
+        state.overlap = config.overlap;
+        state.show_status = config.show_status;
+        state.hide_stderr = config.no_stderr;
+        
+        let errors = config.split_stderr.then(|| {
+            let mut errors = DisplayState::new();
+            errors.follow = config.follow;
+            errors.show_line_numbers = config.line_numbers;
+            errors.wrap = config.wrap;
+            errors.scrollbar = config.scrollbar;
+            errors.show_status = false;
+            errors
+        });
+        
+        let rate = config.rate.then(RateTracker::default);
+        let (sender, results) = mpsc::channel();
+        let mut pane = Self {
+            config,
+            state,
+            rate,
+            sender,
+            results,
+            area,
+            errors,
+            error_ratio: 25,
+            error_focus: false,
+        };
+        pane.seed_content(vec![format!("正在读取: {}", source_label(&pane.config))], Vec::new());
+        pane.start_read();
+        Ok(pane)
+    }
+    
+    fn seed_content(&mut self, content: Vec<String>, streams: Vec<Stream>) {
+        match &mut self.errors {
+            Some(errors) => {
+                (self.state.source_content, errors.source_content) = partition_streams(content, &streams);
+            }
+            None => (self.state.source_content, self.state.source_streams) = (content, streams),
+        }
+        let (content_width, content_height) = self.content_size();
+        self.state.refilter(content_width, content_height);
+        let (error_width, error_height) = self.error_size();
+        if let Some(errors) = &mut self.errors {
+            errors.refilter(error_width, error_height);
+        }
+    }
+    
+    fn areas(&self) -> (Rect, Option<Rect>) {
+        if self.errors.is_none() {
+            return (self.area, None);
+        }
+        let error_height = (self.area.height as u32 * self.error_ratio as u32 / 100).max(2) as u16;
+        let areas = Layout::vertical([Constraint::Min(1), Constraint::Length(error_height)]).split(self.area);
+        (areas[0], Some(areas[1]))
+    }
+    
+    fn content_size(&self) -> (u16, u16) {
+        let (area, _) = self.areas();
+        let content_height = area.height.saturating_sub(self.state.status_height()).max(1);
+        self.state.view_size(area.width, content_height)
+    }
+    
+    fn error_size(&self) -> (u16, u16) {
+        let (Some(errors), (_, Some(area))) = (&self.errors, self.areas()) else {
+            return (0, 0);
+        };
+        let content_height = area.height.saturating_sub(1 + errors.status_height()).max(1);
+        errors.view_size(area.width, content_height)
+    }
+    
+    fn focused_view(&self) -> &DisplayState {
+        match &self.errors {
+            Some(errors) if self.error_focus => errors,
+            _ => &self.state,
+        }
+    }
+    
+    fn is_modal(&self) -> bool {
+        [Some(&self.state), self.errors.as_ref()]
+            .into_iter()
+            .flatten()
+            .any(|view| view.input_mode.is_some() || view.show_help)
+    }
+    
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> bool {
+        let (width, height) = if self.error_focus { self.error_size() } else { self.content_size() };
+        match &mut self.errors {
+            Some(errors) if self.error_focus => errors.handle_key_event(key_event, width, height),
+            _ => self.state.handle_key_event(key_event, width, height),
+        }
+    }
+    
+    fn handle_mouse_event(&mut self, mouse_event: &MouseEvent) -> bool {
+        if let (_, Some(area)) = self.areas() {
+            self.error_focus = mouse_event.row >= area.y;
+        }
+        let (width, height) = if self.error_focus { self.error_size() } else { self.content_size() };
+        match &mut self.errors {
+            Some(errors) if self.error_focus => errors.handle_mouse_event(mouse_event, width, height),
+            _ => self.state.handle_mouse_event(mouse_event, width, height),
+        }
+    }
+    
+    fn adjust_error_ratio(&mut self, grow: bool) {
+        self.error_ratio = if grow {
+            self.error_ratio + ERROR_RATIO_STEP
+        } else {
+            self.error_ratio - ERROR_RATIO_STEP
+        }
+        .clamp(10, 90);
+        self.clamp_scroll();
+    }
+    
+    fn clamp_scroll(&mut self) {
+        let (content_width, content_height) = self.content_size();
+        self.state.clamp_scroll(content_width, content_height);
+        let (error_width, error_height) = self.error_size();
+        if let Some(errors) = &mut self.errors {
+            errors.clamp_scroll(error_width, error_height);
+        }
+    }
+    
+    fn update_content(&mut self, content: Vec<String>, streams: Vec<Stream>) -> bool {
+        let (content_width, content_height) = self.content_size();
+        let (error_width, error_height) = self.error_size();
+        let Some(errors) = &mut self.errors else {
+            return self.state.update_content(content, streams, content_width, content_height);
+        };
+        let (output, error) = partition_streams(content, &streams);
+        let error_changed = errors.update_content(error, Vec::new(), error_width, error_height);
+        self.state.update_content(output, Vec::new(), content_width, content_height) || error_changed
+    }
+    
+    fn adjust_interval(&mut self, faster: bool) {
+        let interval = if faster {
+            self.config.interval.div_f64(INTERVAL_STEP)
+        } else {
+            self.config.interval.mul_f64(INTERVAL_STEP)
+        };
+        let interval = Duration::from_millis((interval.as_secs_f64() * 1000.0).round() as u64);
+        let interval = if faster {
+            interval.max(MIN_INTERVAL).min(self.config.interval)
+        } else {
+            interval.min(MAX_INTERVAL).max(self.config.interval)
+        };
+        self.config.speed *= self.config.interval.as_secs_f64() / interval.as_secs_f64();
+        self.config.interval = interval;
+    }
+    
+    fn prepare_read(&mut self) {
+        if self.config.pty {
+            let (width, height) = self.content_size();
+            self.config.pty_size = (self.state.text_width(width) as u16, self.state.body_height(height).max(1));
+        }
+        self.state.read_started = Instant::now();
+        self.state.drift = self.state.read_started.saturating_duration_since(self.state.last_update + self.config.interval);
+    }
+    
+    fn start_read(&mut self) {
+        self.prepare_read();
+        self.state.reading = true;
+        let config = self.config.clone();
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let _ = sender.send(read_content(&config));
+        });
+    }
+    
+    fn finish_read(&mut self, result: ReadResult) -> io::Result<(Vec<String>, Vec<Stream>)> {
+        self.state.reading = false;
+        self.state.command_status = result.as_ref().ok().and_then(|(_, status)| *status);
+        if self.config.errexit && self.state.failed.is_none() {
+            self.state.failed = match &result {
+                Ok((_, Some(CommandStatus { exit: CommandExit::Code(code), .. }))) if *code != 0 => Some(*code),
+                Err(e) if self.config.command.is_some() => Some(match e.kind() {
+                    io::ErrorKind::NotFound => 127,
+                    io::ErrorKind::PermissionDenied => 126,
+                    _ => 1,
+                }),
+                _ => None,
+            };
+        }
+        let (content, _) = result?;
+        let (streams, content): (Vec<Stream>, Vec<String>) = content.into_iter().unzip();
+        let content = match &mut self.rate {
+            Some(rate) => rate.apply(content),
+            None => content,
+        };
+        if let Err(e) = self.write_log(&content) {
+            self.state.message = Some((format!("写入日志失败: {}", e), Color::Red));
+        }
+        Ok((content, streams))
+    }
+    
+    fn write_log(&mut self, content: &[String]) -> io::Result<()> {
+        let Some(log) = &self.config.log else {
+            return Ok(());
+        };
+        let mut log = log.lock().unwrap();
+        writeln!(
+            log,
+            "{}  ==> {} <==",
+            Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+            source_label(&self.config)
+        )?;
+        for line in content {
+            writeln!(log, "{}", line)?;
+        }
+        log.flush()
+    }
+    
+    fn is_good_read<T>(&self, result: &io::Result<T>) -> bool {
+        match (result, self.state.command_status) {
+            (Err(_), _) => false,
+            (Ok(_), Some(status)) => status.exit == CommandExit::Code(0) && status.has_output,
+            (Ok(_), None) => true,
+        }
+    }
+    
+    fn is_missing_file<T>(&self, result: &io::Result<T>) -> bool {
+        self.config.file.is_some() && matches!(result, Err(e) if e.kind() == io::ErrorKind::NotFound)
+    }
+    
+    fn refresh(&mut self, result: ReadResult) -> bool {
+        let result = self.finish_read(result);
+        let good = self.is_good_read(&result);
+        self.state.file_missing = self.is_missing_file(&result);
+        self.state.stale = self.config.keep_on_error && !good && self.state.has_good_content;
+        if self.state.stale {
+            self.state.mark_updated(self.config.interval);
+            return false;
+        }
+        self.state.has_good_content |= good;
+        if self.state.refresh_count == 0 {
+            let (content, streams) = match result {
+                Ok(content) => content,
+                Err(_) if self.state.file_missing => {
+                    (vec![format!("等待文件出现: {}", self.config.file.as_deref().unwrap_or_default())], Vec::new())
+                }
+                Err(e) => (vec![format!("读取失败: {}", e)], Vec::new()),
+            };
+            self.seed_content(content, streams);
+            self.update_computed();
+            self.state.mark_updated(self.config.interval);
+            return false;
+        }
+        let changed = match result {
+            Ok((new_content, streams)) => {
+                self.state.last_error = None;
+                self.update_content(new_content, streams)
+            }
+            Err(_) if self.state.file_missing => {
+                self.state.last_error = None;
+                false
+            }
+            Err(e) => {
+                self.state.last_error = Some(e.to_string());
+                false
+            }
+        };
+        if changed {
+            self.update_computed();
+        }
+        self.state.stable_count = if changed { 0 } else { self.state.stable_count + 1 };
+        self.state.mark_updated(self.config.interval);
+        changed
+    }
+    
+    fn update_computed(&mut self) {
+        self.state.computed = self.config.compute.as_ref().map(|expr| expr.eval(&self.state.source_content));
+    }
+    
+    fn should_ring(&self) -> bool {
+        match &self.config.beep_on_match {
+            Some(pattern) => self.state.source_content.iter().any(|line| pattern.is_match(&strip_ansi(line))),
+            None => self.config.bell,
+        }
+    }
+    
+    fn should_exit(&self, changed: bool) -> bool {
+        (changed && self.config.chgexit)
+            || self.config.equexit.is_some_and(|cycles| self.state.stable_count >= cycles)
+            || (changed && self.output_matches())
+            || self.config.times.is_some_and(|times| self.state.refresh_count >= times)
+    }
+    
+    fn output_matches(&self) -> bool {
+        let Some(pattern) = &self.config.exit_on_match else {
+            return false;
+        };
+        self.state.source_content.iter().any(|line| pattern.is_match(&strip_ansi(line)))
+    }
+    
+    fn time_until_update(&self, now: Instant) -> Duration {
+        if self.state.reading {
+            Duration::from_millis(20)
+        } else if self.state.paused {
+            Duration::from_millis(100)
+        } else {
+            self.config.interval.saturating_sub(now.duration_since(self.state.last_update))
+        }
+    }
+    
+    fn kill_running(&self) {
+        if let Some(pid) = self.config.running.lock().unwrap().take() {
+            signal_process_group(pid, true);
+        }
+    }
+    
+    fn take_pending_command(&mut self) -> Option<String> {
+        self.state
+            .pending_command
+            .take()
+            .or_else(|| self.errors.as_mut().and_then(|errors| errors.pending_command.take()))
+    }
     
-    Ok(())
-}
-
-fn add_panic() {
-    let orig_hook = panic::take_hook();
-
-    panic::set_hook(Box::new(move |panic_info| {
-        let _ = disable_raw_mode();
-        let mut stdout = io::stdout();
-        let _ = execute!(
-            stdout,
-            LeaveAlternateScreen,
-            cursor::Show
-        );
-        
-        orig_hook(panic_info);
-    }));
+    fn set_command(&mut self, command: String) {
+        if self.config.running.lock().unwrap().is_some() {
+            self.terminate_running();
+        }
+        self.config.command = Some(command);
+        self.config.argv.clear();
+        self.config.file = None;
+        self.config.url = None;
+        self.config.stdin = None;
+        *self.config.file_cache.lock().unwrap() = None;
+        *self.config.decompressed.lock().unwrap() = None;
+        self.state.force_update = true;
+    }
+    
+    fn terminate_running(&mut self) {
+        let Some(pid) = *self.config.running.lock().unwrap() else {
+            self.state.message = Some(("没有正在运行的命令".to_string(), Color::Yellow));
+            return;
+        };
+        signal_process_group(pid, false);
+        let running = Arc::clone(&self.config.running);
+        thread::spawn(move || {
+            thread::sleep(KILL_GRACE);
+            if *running.lock().unwrap() == Some(pid) {
+                signal_process_group(pid, true);
+            }
+        });
+        self.state.message = Some(("已终止正在运行的命令".to_string(), Color::Yellow));
+    }
 }
 
 impl App {
-    fn new(config: AppConfig) -> io::Result<Self> {
-        let terminal = setup_terminal()?;
-        let mut state = DisplayState::new();
-        
-        match read_content(&config) {
-            Ok(content) => {
-                state.content = content;
-            }
-            Err(e) => {
-                state.content = vec![format!("读取失败: {}", e)];
-            }
-        }
-        
+    fn new(configs: Vec<AppConfig>) -> io::Result<Self> {
+        let terminal = setup_terminal(configs.iter().any(|config| config.mouse))?;
+        let history_path = configs.iter().all(|config| config.history).then(history_path).flatten();
+        let history = history_path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|text| text.lines().filter(|line| !line.is_empty()).map(String::from).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let history = history[history.len().saturating_sub(HISTORY_LIMIT)..].to_vec();
+        let resized = Arc::new(AtomicBool::new(false));
+        #[cfg(unix)]
+        signal_hook::flag::register(signal_hook::consts::SIGWINCH, Arc::clone(&resized))?;
+        let areas = pane_areas(terminal.size()?, configs.len());
+        let panes = configs
+            .into_iter()
+            .zip(areas)
+            .map(|(config, area)| Pane::new(config, area))
+            .collect::<io::Result<Vec<_>>>()?;
         Ok(Self {
-            config,
-            state,
+            panes,
+            focus: 0,
             terminal,
+            exit_code: 0,
+            history,
+            history_index: None,
+            history_path,
+            resized,
         })
     }
     
+    fn resize(&mut self) -> io::Result<()> {
+        self.terminal.autoresize()?;
+        self.layout()?;
+        for pane in &mut self.panes {
+            if pane.config.pty {
+                pane.state.force_update = true;
+            }
+        }
+        Ok(())
+    }
+    
+    fn browse_history(&mut self, older: bool) {
+        let len = self.history.len();
+        self.history_index = match self.history_index {
+            _ if len == 0 => None,
+            None if older => Some(len - 1),
+            None => None,
+            Some(index) if older => Some(index.saturating_sub(1)),
+            Some(index) if index + 1 < len => Some(index + 1),
+            Some(_) => None,
+        };
+        let input = self.history_index.map(|index| self.history[index].clone()).unwrap_or_default();
+        let pane = &mut self.panes[self.focus];
+        match &mut pane.errors {
+            Some(errors) if pane.error_focus => errors.input = input,
+            _ => pane.state.input = input,
+        }
+    }
+    
+    fn run_command(&mut self, command: String) {
+        if self.history.last() != Some(&command) {
+            self.history.push(command.clone());
+            let excess = self.history.len().saturating_sub(HISTORY_LIMIT);
+            self.history.drain(..excess);
+        }
+        let pane = &mut self.panes[self.focus];
+        if let Err(e) = save_history(self.history_path.as_deref(), &self.history) {
+            pane.state.message = Some((format!("写入历史失败: {}", e), Color::Red));
+        }
+        pane.set_command(command);
+    }
+    
+    fn layout(&mut self) -> io::Result<()> {
+        let areas = pane_areas(self.terminal.size()?, self.panes.len());
+        for (pane, area) in self.panes.iter_mut().zip(areas) {
+            pane.area = area;
+            pane.clamp_scroll();
+        }
+        Ok(())
+    }
+    
+    fn draw(&mut self) -> io::Result<()> {
+        let panes = &self.panes;
+        let focus = self.focus;
+        self.terminal.draw(|frame| {
+            frame.render_widget(Clear, frame.size());
+            for (index, pane) in panes.iter().enumerate() {
+                let focused = panes.len() == 1 || index == focus;
+                let (area, error_area) = pane.areas();
+                render_ui(frame, &pane.config, &pane.state, area, focused && !pane.error_focus);
+                if let (Some(errors), Some(area)) = (&pane.errors, error_area) {
+                    render_error_pane(frame, &pane.config, errors, area, focused && pane.error_focus);
+                }
+            }
+            let view = panes[focus].focused_view();
+            if view.show_help {
+                render_help(frame, &panes[focus].config, view.help_scroll, frame.size());
+            }
+        })?;
+        for pane in &mut self.panes {
+            pane.state.mark_rendered();
+        }
+        Ok(())
+    }
+    
+    fn ring_bell(&mut self, index: usize) -> io::Result<()> {
+        let backend = self.terminal.backend_mut();
+        backend.write_all(b"\x07")?;
+        io::Write::flush(backend)?;
+        self.panes[index].state.bell_at = Some(Instant::now());
+        Ok(())
+    }
+    
+    fn failed(&self) -> Option<i32> {
+        self.panes.iter().find_map(|pane| pane.state.failed)
+    }
+    
     fn run(&mut self) -> io::Result<()> {
-        loop {
-            let now = Instant::now();
-            let time_since_last_update = now.duration_since(self.state.last_update);
-            let time_until_next_update = if time_since_last_update >= self.config.interval {
-                Duration::from_millis(0)
-            } else {
-                self.config.interval - time_since_last_update
-            };
+        'run: loop {
+            for index in 0..self.panes.len() {
+                let Ok(result) = self.panes[index].results.try_recv() else {
+                    continue;
+                };
+                let first = self.panes[index].state.refresh_count == 0;
+                let changed = self.panes[index].refresh(result);
+                if changed && self.panes[index].should_ring() {
+                    self.ring_bell(index)?;
+                }
+                if self.panes[index].should_exit(changed) || (first && self.panes[index].output_matches()) {
+                    break 'run;
+                }
+            }
             
-            let poll_timeout = time_until_next_update.min(Duration::from_millis(100));
+            let now = Instant::now();
+            let poll_timeout = self
+                .panes
+                .iter()
+                .map(|pane| pane.time_until_update(now))
+                .min()
+                .unwrap_or_default()
+                .min(Duration::from_millis(100));
             
-            if time_since_last_update >= self.config.interval {
-                match read_content(&self.config) {
-                    Ok(new_content) => {
-                        let size = self.terminal.size()?;
-                        let content_height = if size.height >= 2 {
-                            size.height - 1
-                        } else {
-                            1
-                        };
-                        let content_width = size.width;
-                        self.state.update_content(new_content, content_width, content_height);
-                    }
-                    Err(e) => {
-                        self.state.content = vec![format!("读取失败: {}", e)];
-                    }
+            for pane in &mut self.panes {
+                if !pane.state.reading && pane.state.should_update(now, pane.config.interval) {
+                    pane.start_read();
                 }
-                self.state.mark_updated();
             }
             
-            self.terminal.draw(|frame| {
-                render_ui(frame, &self.config, &self.state);
-            })?;
-            self.state.mark_rendered();
+            if self.resized.swap(false, atomic::Ordering::Relaxed) {
+                self.resize()?;
+            }
+            self.draw()?;
             
             if event::poll(poll_timeout)? {
                 match event::read()? {
                     Event::Key(key_event) => {
+                        if self.failed().is_some() && key_event.kind == KeyEventKind::Press {
+                            break;
+                        }
+                        
                         let is_ctrl_c = key_event.modifiers.contains(KeyModifiers::CONTROL) 
                             && key_event.code == KeyCode::Char('c');
+                        let pane = &mut self.panes[self.focus];
+                        
+                        if pane.state.confirming_quit && !is_ctrl_c {
+                            if key_event.kind == KeyEventKind::Press {
+                                pane.state.confirming_quit = false;
+                                if matches!(key_event.code, KeyCode::Char('y' | 'Y')) {
+                                    if pane.config.exit_on_match.is_some() {
+                                        self.exit_code = 130;
+                                    }
+                                    break;
+                                }
+                            }
+                            continue;
+                        }
                         
-                        if is_ctrl_c || key_event.code == KeyCode::Char('q') {
+                        let modal = pane.is_modal();
+                        let is_quit = key_event.code == KeyCode::Char('q') && !modal;
+                        if is_quit && pane.config.confirm_quit {
+                            if key_event.kind == KeyEventKind::Press {
+                                pane.state.confirming_quit = true;
+                            }
+                            continue;
+                        }
+                        if is_ctrl_c || is_quit {
+                            if self.panes.iter().any(|pane| pane.config.exit_on_match.is_some()) {
+                                self.exit_code = 130;
+                            }
                             break;
                         }
 
-                        let size = self.terminal.size()?;
-                        let content_height = if size.height >= 2 {
-                            size.height - 1
-                        } else {
-                            1
-                        };
-                        let content_width = size.width;
-                        let handled = self.state.handle_key_event(&key_event, content_width, content_height);
+                        if matches!(key_event.code, KeyCode::Tab | KeyCode::BackTab)
+                            && key_event.kind == KeyEventKind::Press
+                            && !modal
+                        {
+                            let forward = key_event.code == KeyCode::Tab;
+                            if forward && pane.errors.is_some() && !pane.error_focus {
+                                pane.error_focus = true;
+                            } else if !forward && pane.error_focus {
+                                pane.error_focus = false;
+                            } else {
+                                pane.error_focus = false;
+                                let count = self.panes.len();
+                                self.focus = if forward {
+                                    (self.focus + 1) % count
+                                } else {
+                                    (self.focus + count - 1) % count
+                                };
+                                let pane = &mut self.panes[self.focus];
+                                pane.error_focus = !forward && pane.errors.is_some();
+                            }
+                            self.draw()?;
+                            continue;
+                        }
+
+                        if key_event.code == KeyCode::Char('r')
+                            && key_event.kind == KeyEventKind::Press
+                            && !modal
+                        {
+                            if !pane.state.reading {
+                                pane.start_read();
+                            }
+                            self.draw()?;
+                            continue;
+                        }
+
+                        let is_ctrl_k = key_event.modifiers.contains(KeyModifiers::CONTROL)
+                            && key_event.code == KeyCode::Char('k');
+                        if (is_ctrl_k || key_event.code == KeyCode::Char('x'))
+                            && key_event.kind == KeyEventKind::Press
+                            && !modal
+                        {
+                            pane.terminate_running();
+                            self.draw()?;
+                            continue;
+                        }
+
+                        if matches!(key_event.code, KeyCode::Char('+' | '=' | '-'))
+                            && key_event.kind == KeyEventKind::Press
+                            && !modal
+                        {
+                            pane.adjust_interval(key_event.code != KeyCode::Char('-'));
+                            self.draw()?;
+                            continue;
+                        }
+
+                        if key_event.code == KeyCode::Char('t')
+                            && key_event.kind == KeyEventKind::Press
+                            && !modal
+                        {
+                            pane.state.show_status = !pane.state.show_status;
+                            let (content_width, content_height) = pane.content_size();
+                            pane.state.clamp_scroll(content_width, content_height);
+                            self.draw()?;
+                            continue;
+                        }
+
+                        if matches!(key_event.code, KeyCode::Char('[' | ']'))
+                            && key_event.kind == KeyEventKind::Press
+                            && pane.errors.is_some()
+                            && !modal
+                        {
+                            pane.adjust_error_ratio(key_event.code == KeyCode::Char(']'));
+                            self.draw()?;
+                            continue;
+                        }
+
+                        if pane.focused_view().input_mode != Some(InputMode::Command) {
+                            self.history_index = None;
+                        } else if matches!(key_event.code, KeyCode::Up | KeyCode::Down)
+                            && key_event.kind == KeyEventKind::Press
+                        {
+                            self.browse_history(key_event.code == KeyCode::Up);
+                            self.draw()?;
+                            continue;
+                        }
+
+                        let handled = pane.handle_key_event(&key_event);
+                        if let Some(command) = pane.take_pending_command() {
+                            self.run_command(command);
+                        }
                         
                         if handled {
-                            self.terminal.draw(|frame| {
-                                render_ui(frame, &self.config, &self.state);
-                            })?;
+                            self.draw()?;
                         }
                     }
                     
-                    Event::Resize(new_width, new_height) => {
-                        let content_height = if new_height >= 2 {
-                            new_height - 1
-                        } else {
-                            1
-                        };
-                        let content_width = new_width;
-                        self.state.update_content(
-                            self.state.content.clone(),
-                            content_width,
-                            content_height
-                        );
-                        self.terminal.draw(|frame| {
-                            render_ui(frame, &self.config, &self.state);
-                        })?;
+                    Event::Mouse(mouse_event) => {
+                        if let Some(index) = self
+                            .panes
+                            .iter()
+                            .position(|pane| mouse_event.column < pane.area.x + pane.area.width)
+                        {
+                            self.focus = index;
+                        }
+                        if self.panes[self.focus].handle_mouse_event(&mouse_event) {
+                            self.draw()?;
+                        }
+                    }
+                    
+                    Event::Resize(_, _) => {
+                        self.resize()?;
+                        self.draw()?;
                     }
                     _ => {}
                 }
@@ -690,18 +4276,678 @@ impl App {
     }
     
     fn cleanup(mut self) -> io::Result<()> {
+        for pane in &self.panes {
+            pane.kill_running();
+        }
         restore_terminal(&mut self.terminal)
     }
 }
 
+fn partition_streams(content: Vec<String>, streams: &[Stream]) -> (Vec<String>, Vec<String>) {
+    let mut output = Vec::new();
+    let mut errors = Vec::new();
+    for (index, line) in content.into_iter().enumerate() {
+        if streams.get(index) == Some(&Stream::Stderr) {
+            errors.push(line);
+        } else {
+            output.push(line);
+        }
+    }
+    (output, errors)
+}
+
+fn history_path() -> Option<std::path::PathBuf> {
+    let state = match std::env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => std::path::PathBuf::from(dir),
+        _ => std::path::PathBuf::from(std::env::var_os("HOME")?).join(".local/state"),
+    };
+    Some(state.join("grain/history"))
+}
+
+fn save_history(path: Option<&std::path::Path>, history: &[String]) -> io::Result<()> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = File::create(path)?;
+    for command in history {
+        writeln!(file, "{}", command)?;
+    }
+    Ok(())
+}
+
+fn pane_areas(area: Rect, count: usize) -> Vec<Rect> {
+    Layout::horizontal(vec![Constraint::Ratio(1, count.max(1) as u32); count])
+        .spacing(1)
+        .split(area)
+        .to_vec()
+}
+
+fn print_once(configs: &[AppConfig]) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    for (index, config) in configs.iter().enumerate() {
+        if configs.len() > 1 {
+            if index > 0 {
+                writeln!(out)?;
+            }
+            writeln!(out, "==> {} <==", source_label(config))?;
+        }
+        for line in once_lines(config, read_content(config)?.0) {
+            writeln!(out, "{}", line)?;
+        }
+    }
+    out.flush()
+}
+
+fn once_lines(config: &AppConfig, content: Vec<(Stream, String)>) -> Vec<String> {
+    let mut state = DisplayState::new();
+    state.header_lines = config.header;
+    state.filters = config.filters.clone();
+    state.hide_stderr = config.no_stderr;
+    state.sort = config.sort;
+    (state.source_streams, state.source_content) = content.into_iter().unzip();
+    let mut lines: Vec<String> = state.source_content.iter().take(state.header_lines).cloned().collect();
+    lines.extend(state.filtered_content().0);
+    lines
+}
+
 fn main() -> io::Result<()> {
     add_panic();
     
-    let config = parse_args();
+    let mut configs = parse_args(std::env::args_os());
+    let piped = configs.len() == 1
+        && configs[0].command.is_none()
+        && configs[0].file.is_none()
+        && configs[0].url.is_none()
+        && !io::stdin().is_terminal();
+    
+    if configs[0].once || !io::stdout().is_terminal() {
+        if piped {
+            let lines = io::stdin().lock().lines().collect::<io::Result<Vec<String>>>()?;
+            configs[0].stdin = Some(Arc::new(Mutex::new(lines)));
+        }
+        return print_once(&configs);
+    }
+    
+    if piped {
+        configs[0].stdin = Some(spawn_stdin_reader());
+    }
     
-    let mut app = App::new(config)?;
+    let mut app = App::new(configs)?;
     app.run()?;
+    let exit_code = app.failed().unwrap_or(app.exit_code);
     app.cleanup()?;
     
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn config(args: &[&str]) -> AppConfig {
+        parse_args(std::iter::once("grain").chain(args.iter().copied())).remove(0)
+    }
+    
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("grain-test-{}-{}", std::process::id(), name))
+    }
+    
+    fn append(path: &std::path::Path, text: &str) {
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+    
+    #[test]
+    fn read_file_appends_new_lines() {
+        let path = temp_path("append.log");
+        std::fs::write(&path, "a\nb\n").unwrap();
+        let config = config(&["-f", path.to_str().unwrap()]);
+        let file = path.to_str().unwrap();
+        assert_eq!(read_file(&config, file).unwrap(), ["a", "b"]);
+        append(&path, "c\nd");
+        assert_eq!(read_file(&config, file).unwrap(), ["a", "b", "c", "d"]);
+        append(&path, "e\n");
+        assert_eq!(read_file(&config, file).unwrap(), ["a", "b", "c", "de"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    fn read_file_rereads_after_rewrite_in_place() {
+        let path = temp_path("rewrite.log");
+        std::fs::write(&path, "count 10\n").unwrap();
+        let config = config(&["-f", path.to_str().unwrap()]);
+        let file = path.to_str().unwrap();
+        assert_eq!(read_file(&config, file).unwrap(), ["count 10"]);
+        std::fs::write(&path, "count 11\n").unwrap();
+        assert_eq!(read_file(&config, file).unwrap(), ["count 11"]);
+        std::fs::write(&path, "count 12 more\n").unwrap();
+        assert_eq!(read_file(&config, file).unwrap(), ["count 12 more"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    fn read_file_rereads_after_truncate() {
+        let path = temp_path("truncate.log");
+        std::fs::write(&path, "a\nb\nc\n").unwrap();
+        let config = config(&["-f", path.to_str().unwrap()]);
+        let file = path.to_str().unwrap();
+        assert_eq!(read_file(&config, file).unwrap(), ["a", "b", "c"]);
+        std::fs::write(&path, "x\n").unwrap();
+        assert_eq!(read_file(&config, file).unwrap(), ["x"]);
+        append(&path, "y\n");
+        assert_eq!(read_file(&config, file).unwrap(), ["x", "y"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    fn read_file_rereads_after_rotate() {
+        let path = temp_path("rotate.log");
+        let rotated = temp_path("rotate.log.1");
+        std::fs::write(&path, "old 1\nold 2\n").unwrap();
+        let config = config(&["-f", path.to_str().unwrap()]);
+        let file = path.to_str().unwrap();
+        assert_eq!(read_file(&config, file).unwrap(), ["old 1", "old 2"]);
+        std::fs::rename(&path, &rotated).unwrap();
+        std::fs::write(&path, "new 1\nnew 2\nnew 3\n").unwrap();
+        assert_eq!(read_file(&config, file).unwrap(), ["new 1", "new 2", "new 3"]);
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&rotated).unwrap();
+    }
+    
+    #[test]
+    fn read_file_keeps_tail_limit() {
+        let path = temp_path("tail.log");
+        std::fs::write(&path, "1\n2\n3\n").unwrap();
+        let config = config(&["-f", path.to_str().unwrap(), "--tail", "2"]);
+        let file = path.to_str().unwrap();
+        assert_eq!(read_file(&config, file).unwrap(), ["2", "3"]);
+        append(&path, "4\n");
+        assert_eq!(read_file(&config, file).unwrap(), ["3", "4"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn mark_updated_schedules_from_completion_by_default() {
+        let mut state = DisplayState::new();
+        let interval = Duration::from_millis(100);
+        state.read_started = Instant::now() - Duration::from_millis(250);
+        state.mark_updated(interval);
+        let now = Instant::now();
+        assert!(!state.should_update(now, interval));
+        assert!(state.should_update(now + interval, interval));
+    }
+
+    #[test]
+    fn mark_updated_refreshes_right_after_overrun_under_wait() {
+        let interval = Duration::from_millis(100);
+        for precise in [false, true] {
+            let mut state = DisplayState::new();
+            state.precise = precise;
+            state.overlap = OverlapPolicy::Wait;
+            state.read_started = Instant::now() - Duration::from_millis(250);
+            state.mark_updated(interval);
+            assert!(state.should_update(Instant::now(), interval));
+            
+            let mut state = DisplayState::new();
+            state.precise = precise;
+            state.overlap = OverlapPolicy::Skip;
+            state.read_started = Instant::now() - Duration::from_millis(250);
+            state.mark_updated(interval);
+            assert!(!state.should_update(Instant::now(), interval));
+            assert_eq!(state.skipped, 2);
+        }
+    }
+    
+    #[test]
+    fn mark_updated_waits_a_full_interval_without_overrun() {
+        let interval = Duration::from_secs(1);
+        let mut state = DisplayState::new();
+        state.overlap = OverlapPolicy::Wait;
+        state.read_started = Instant::now();
+        state.mark_updated(interval);
+        assert!(!state.should_update(Instant::now(), interval));
+    }
+    
+    #[test]
+    fn visual_width_skips_escape_sequences() {
+        assert_eq!(visual_width("\x1b[01;31m\x1b[Kmatch\x1b[m\x1b[K rest"), 10);
+        assert_eq!(visual_width("\x1b(Bplain\x1b)0"), 5);
+        assert_eq!(visual_width("\x1b]8;;http://x\x07link\x1b]8;;\x1b\\"), 4);
+        assert_eq!(visual_width("\x1b[38;2;1;2;3mrgb\x1b[0m"), 3);
+    }
+    
+    #[test]
+    fn visual_width_counts_wide_chars() {
+        assert_eq!(visual_width("abc"), 3);
+        assert_eq!(visual_width("中文"), 4);
+        assert_eq!(visual_width("a中b文c"), 7);
+        assert_eq!(visual_width("\x1b[32m日本\x1b[0m ok"), 7);
+    }
+    
+    #[test]
+    fn strip_ansi_and_parse_ansi_line_agree() {
+        let line = "\x1b(B\x1b[1;31mred\x1b[K\x1b[m plain";
+        assert_eq!(strip_ansi(line), "red plain");
+        let parsed = parse_ansi_line(line);
+        let text: String = parsed.spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(text, "red plain");
+        assert_eq!(parsed.spans[0].style, Style::default().fg(Color::Red).add_modifier(Modifier::BOLD));
+        assert_eq!(parsed.spans[1].style, Style::default());
+    }
+
+    #[test]
+    fn expand_tabs_ignores_escape_sequences() {
+        assert_eq!(expand_tabs("\x1b[K\tx\tmy", 4), "\x1b[K    x   my");
+        assert_eq!(expand_tabs("\x1b[1;31mab\x1b[0m\tc", 4), "\x1b[1;31mab\x1b[0m  c");
+        assert_eq!(expand_tabs("中\tx", 4), "中  x");
+        assert_eq!(expand_tabs("\x1b(B\tx", 8), "\x1b(B        x");
+    }
+
+    #[test]
+    fn once_lines_applies_filters_and_header() {
+        let grep = config(&["--once", "-c", "seq 1 5", "--grep", "3", "-v"]);
+        assert_eq!(once_lines(&grep, read_content(&grep).unwrap().0), ["1", "2", "4", "5"]);
+        
+        let header = config(&["--once", "-c", "seq 1 6", "--header", "1", "--filter", "[246]", "--filter-out", "4"]);
+        assert_eq!(once_lines(&header, read_content(&header).unwrap().0), ["1", "2", "6"]);
+    }
+    
+    #[test]
+    fn once_lines_hides_stderr() {
+        let content = vec![(Stream::Stdout, "out".to_string()), (Stream::Stderr, "err".to_string())];
+        assert_eq!(once_lines(&config(&["--once", "--no-stderr", "-c", "true"]), content), ["out"]);
+    }
+
+    #[test]
+    fn once_lines_sorts_after_header() {
+        let content = ["name", "b 10", "a 9", "c 100"].map(|line| (Stream::Stdout, line.to_string())).to_vec();
+        let lines = once_lines(&config(&["--once", "-c", "true", "--header", "1", "--sort", "2"]), content.clone());
+        assert_eq!(lines, ["name", "b 10", "c 100", "a 9"]);
+        let lines = once_lines(&config(&["--once", "-c", "true", "--header", "1", "--sort", "2", "--sort-numeric", "--sort-reverse"]), content);
+        assert_eq!(lines, ["name", "c 100", "b 10", "a 9"]);
+    }
+
+    #[test]
+    fn wrapped_rows_match_line_lengths() {
+        let mut state = DisplayState::new();
+        state.wrap = true;
+        state.source_content = ["short", "a much longer line that wraps", "中文中文中文中文", ""].map(String::from).to_vec();
+        state.refilter(10, 5);
+        let rows: Vec<usize> = state.content.iter().map(|line| wrap_row_count(line, 10)).collect();
+        let mut row = 0;
+        for (index, &count) in rows.iter().enumerate() {
+            assert_eq!(state.row_offset(index, 10), row);
+            for offset in 0..count {
+                assert_eq!(state.line_at_row(row + offset, 10), (index, offset));
+            }
+            row += count;
+        }
+        assert_eq!(state.line_at_row(row, 10), (state.content.len(), 0));
+        assert_eq!(state.max_scroll(10, 5), (0, (row - 5) as u16));
+    }
+    
+    #[test]
+    fn max_scroll_uses_measured_widths() {
+        let mut state = DisplayState::new();
+        state.freeze_cols = 1;
+        state.source_content = ["id value", "\x1b[31mlonger\x1b[0m row of text"].map(String::from).to_vec();
+        state.refilter(10, 5);
+        assert_eq!(state.frozen_width(), 6);
+        assert_eq!(state.max_scroll(10, 5), (18 - 6 - 3, 0));
+        state.source_content.truncate(1);
+        state.refilter(10, 5);
+        assert_eq!(state.max_scroll(10, 5), (0, 0));
+    }
+
+    #[test]
+    fn env_values_are_passed_literally() {
+        let config = config(&["-c", "true", "--env", "GREETING=$HOME/x", "--strict-env", "--env", "EMPTY="]);
+        assert_eq!(config.env, [("GREETING".to_string(), "$HOME/x".to_string()), ("EMPTY".to_string(), String::new())]);
+    }
+
+    #[test]
+    fn should_update_does_not_catch_up_after_a_long_gap() {
+        let interval = Duration::from_secs(1);
+        let mut state = DisplayState::new();
+        let gap = Duration::from_secs(3600);
+        state.last_update = Instant::now() - gap;
+        state.read_started = Instant::now();
+        assert!(state.should_update(Instant::now(), interval));
+        state.mark_updated(interval);
+        let now = Instant::now();
+        assert!(!state.should_update(now, interval));
+        assert!(!state.should_update(now + interval / 2, interval));
+        assert!(state.should_update(now + interval, interval));
+    }
+    
+    #[test]
+    fn should_update_precise_skips_missed_slots() {
+        let interval = Duration::from_secs(1);
+        let mut state = DisplayState::new();
+        state.precise = true;
+        state.started = Instant::now() - Duration::from_secs(3600) - interval / 2;
+        state.last_update = state.started;
+        state.read_started = Instant::now();
+        assert!(state.should_update(Instant::now(), interval));
+        state.mark_updated(interval);
+        let now = Instant::now();
+        assert!(!state.should_update(now, interval));
+        assert!(state.should_update(now + interval, interval));
+        assert_eq!(state.skipped, 0);
+    }
+    
+    #[test]
+    fn should_update_waits_while_paused() {
+        let interval = Duration::from_millis(100);
+        let mut state = DisplayState::new();
+        state.last_update = Instant::now() - Duration::from_secs(60);
+        state.toggle_pause();
+        assert!(!state.should_update(Instant::now(), interval));
+        state.toggle_pause();
+        assert!(state.should_update(Instant::now(), interval));
+        state.mark_updated(interval);
+        assert!(!state.should_update(Instant::now(), interval));
+    }
+
+    #[test]
+    fn parse_interval_accepts_minutes_and_hours() {
+        assert_eq!(parse_interval("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_interval("1h"), Ok(Duration::from_secs(3600)));
+        assert_eq!(parse_interval("1.5m"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_interval("0.5h"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_interval("1H 30M"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_interval("2.5"), Ok(Duration::from_millis(2500)));
+    }
+    
+    #[test]
+    fn parse_interval_rejects_zero_and_garbage() {
+        assert!(parse_interval("0m").is_err());
+        assert!(parse_interval("0h").is_err());
+        assert!(parse_interval("").is_err());
+        assert!(parse_interval("-1m").is_err());
+        assert!(parse_interval("m").is_err());
+        assert!(parse_interval("1d").is_err());
+    }
+
+    #[test]
+    fn parse_duration_parts_sums_compound_forms() {
+        assert_eq!(parse_duration_parts("1h30m"), Ok(5_400_000.0));
+        assert_eq!(parse_duration_parts("1m30s500ms"), Ok(90_500.0));
+        assert_eq!(parse_duration_parts("2s2s"), Ok(4_000.0));
+        assert_eq!(parse_duration_parts("0.25s"), Ok(250.0));
+    }
+    
+    #[test]
+    fn parse_duration_parts_rejects_bad_tokens() {
+        assert_eq!(parse_duration_parts("1m30"), Err("缺少时间单位: 30".to_string()));
+        assert_eq!(parse_duration_parts("5x"), Err("不支持的时间单位: 5x".to_string()));
+        assert_eq!(parse_duration_parts("1.2.3s"), Err("无效的时间值: 1.2.3s".to_string()));
+        assert_eq!(parse_duration_parts("s"), Err("无效的时间值: s".to_string()));
+    }
+    
+    #[test]
+    fn parse_interval_rounds_and_enforces_floor() {
+        assert_eq!(parse_interval("0.1234s"), Ok(Duration::from_millis(123)));
+        assert_eq!(parse_interval("99.6ms"), Ok(Duration::from_millis(100)));
+        assert_eq!(parse_interval("100ms"), Ok(Duration::from_millis(100)));
+        assert_eq!(parse_interval("99.4ms"), Err("间隔不能小于100毫秒".to_string()));
+        assert_eq!(parse_interval("0.05"), Err("间隔不能小于100毫秒".to_string()));
+    }
+    
+    #[test]
+    fn format_interval_uses_compound_units() {
+        assert_eq!(format_interval(Duration::from_millis(100)), "100ms");
+        assert_eq!(format_interval(Duration::from_millis(1500)), "1.5s");
+        assert_eq!(format_interval(Duration::from_secs(90)), "1m30s");
+        assert_eq!(format_interval(Duration::from_secs(3600)), "1h");
+        assert_eq!(format_interval(Duration::from_millis(3_661_001)), "1h1m1.001s");
+        assert_eq!(format_interval(Duration::from_micros(1_500_400)), "1.5s");
+    }
+    
+    #[test]
+    fn format_interval_round_trips_through_parse() {
+        for text in ["100ms", "2.5s", "1m30s", "1h", "1h1m1.001s"] {
+            assert_eq!(format_interval(parse_interval(text).unwrap()), text);
+        }
+    }
+
+    #[test]
+    fn apply_sgr_parses_extended_colors() {
+        let base = Style::default();
+        assert_eq!(apply_sgr(base, "38;2;255;128;0"), base.fg(Color::Rgb(255, 128, 0)));
+        assert_eq!(apply_sgr(base, "48;2;1;2;3"), base.bg(Color::Rgb(1, 2, 3)));
+        assert_eq!(apply_sgr(base, "38;5;208"), base.fg(Color::Indexed(208)));
+        assert_eq!(apply_sgr(base, "1;48;5;17;38;2;10;20;30"), base.add_modifier(Modifier::BOLD).bg(Color::Indexed(17)).fg(Color::Rgb(10, 20, 30)));
+        assert_eq!(apply_sgr(base, "38;2;1;2"), base);
+    }
+    
+    #[test]
+    fn apply_sgr_resets_and_clears_colors() {
+        let style = apply_sgr(Style::default(), "1;31;44");
+        assert_eq!(style, Style::default().add_modifier(Modifier::BOLD).fg(Color::Red).bg(Color::Blue));
+        assert_eq!(apply_sgr(style, "39"), Style::default().add_modifier(Modifier::BOLD).bg(Color::Blue));
+        assert_eq!(apply_sgr(style, "49;22"), Style::default().fg(Color::Red).remove_modifier(Modifier::BOLD | Modifier::DIM));
+        assert_eq!(apply_sgr(style, "0"), Style::default());
+        assert_eq!(apply_sgr(style, ""), Style::default());
+        assert_eq!(apply_sgr(Style::default(), "92;103"), Style::default().fg(Color::LightGreen).bg(Color::LightYellow));
+    }
+    
+    #[test]
+    fn parse_ansi_line_styles_truecolor_spans() {
+        let line = parse_ansi_line("\x1b[38;2;255;0;0mred\x1b[48;5;236m on grey\x1b[0m");
+        assert_eq!(line.spans.len(), 2);
+        assert_eq!(line.spans[0].content, "red");
+        assert_eq!(line.spans[0].style, Style::default().fg(Color::Rgb(255, 0, 0)));
+        assert_eq!(line.spans[1].content, " on grey");
+        assert_eq!(line.spans[1].style, Style::default().fg(Color::Rgb(255, 0, 0)).bg(Color::Indexed(236)));
+    }
+
+    #[test]
+    fn spawn_line_reader_splits_lines() {
+        let (sender, receiver) = mpsc::channel();
+        spawn_line_reader(io::Cursor::new(b"one\r\ntwo\n\xff\nlast".to_vec()), true, sender);
+        let lines: Vec<(bool, String)> = receiver.iter().collect();
+        assert_eq!(lines, [
+            (true, "one".to_string()),
+            (true, "two".to_string()),
+            (true, "\u{fffd}".to_string()),
+            (true, "last".to_string()),
+        ]);
+    }
+    
+    #[test]
+    fn read_content_keeps_stdout_and_stderr_order() {
+        let script = "for i in 1 2 3; do echo out$i; sleep 0.05; echo err$i >&2; sleep 0.05; done";
+        let config = config(&["-c", script]);
+        let (lines, status) = read_content(&config).unwrap();
+        let lines: Vec<(Stream, String)> = lines.into_iter().map(|(stream, line)| (stream, strip_ansi(&line))).collect();
+        let expected: Vec<(Stream, String)> = (1..=3)
+            .flat_map(|i| [(Stream::Stdout, format!("out{}", i)), (Stream::Stderr, format!("err{}", i))])
+            .collect();
+        assert_eq!(lines, expected);
+        assert_eq!(status.unwrap().exit, CommandExit::Code(0));
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+    
+    #[test]
+    fn read_file_decompresses_gzip() {
+        let path = temp_path("fixture.log.gz");
+        std::fs::write(&path, gzip(b"first\r\nsecond\nthird")).unwrap();
+        let config = config(&["-f", path.to_str().unwrap()]);
+        assert_eq!(read_file(&config, path.to_str().unwrap()).unwrap(), ["first", "second", "third"]);
+        assert_eq!(*config.decompressed.lock().unwrap(), Some((Compression::Gzip, 19)));
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    fn read_file_detects_gzip_by_magic() {
+        let path = temp_path("fixture-no-extension");
+        std::fs::write(&path, gzip(b"hidden\n")).unwrap();
+        let config = config(&["-f", path.to_str().unwrap()]);
+        assert_eq!(read_file(&config, path.to_str().unwrap()).unwrap(), ["hidden"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    fn read_compressed_caps_output_at_max_bytes() {
+        let path = temp_path("large.log.gz");
+        std::fs::write(&path, gzip("line\n".repeat(1000).as_bytes())).unwrap();
+        let config = config(&["-f", path.to_str().unwrap(), "--max-bytes", "1K"]);
+        let lines = read_compressed(&config, path.to_str().unwrap(), File::open(&path).unwrap(), Compression::Gzip).unwrap();
+        assert_eq!(lines.len(), 1024 / 5 + 2);
+        assert!(strip_ansi(lines.last().unwrap()).starts_with("[已截断]"));
+        assert_eq!(*config.decompressed.lock().unwrap(), Some((Compression::Gzip, 1024)));
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    fn read_file_reports_corrupt_gzip() {
+        let path = temp_path("broken.log.gz");
+        let data = gzip("line\n".repeat(100).as_bytes());
+        std::fs::write(&path, &data[..data.len() / 2]).unwrap();
+        let config = config(&["-f", path.to_str().unwrap()]);
+        let error = read_file(&config, path.to_str().unwrap()).unwrap_err();
+        assert!(error.to_string().contains("解压失败 (gzip)"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn read_file_decompresses_zstd() {
+        let path = temp_path("fixture.log.zst");
+        std::fs::write(&path, zstd::encode_all(&b"alpha\nbeta\n"[..], 0).unwrap()).unwrap();
+        let config = config(&["-f", path.to_str().unwrap()]);
+        assert_eq!(read_file(&config, path.to_str().unwrap()).unwrap(), ["alpha", "beta"]);
+        assert_eq!(*config.decompressed.lock().unwrap(), Some((Compression::Zstd, 11)));
+        
+        let magic = temp_path("fixture-zstd");
+        std::fs::rename(&path, &magic).unwrap();
+        assert_eq!(read_file(&config, magic.to_str().unwrap()).unwrap(), ["alpha", "beta"]);
+        std::fs::remove_file(&magic).unwrap();
+    }
+    
+    #[test]
+    fn read_file_reports_corrupt_zstd() {
+        let path = temp_path("broken.log.zst");
+        std::fs::write(&path, b"not zstd data").unwrap();
+        let config = config(&["-f", path.to_str().unwrap()]);
+        let error = read_file(&config, path.to_str().unwrap()).unwrap_err();
+        assert!(error.to_string().contains("解压失败 (zstd)"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pty_is_the_controlling_terminal() {
+        let config = config(&["--pty", "-c", ": </dev/tty && ps -o stat= -p $$"]);
+        let (lines, status) = read_content(&config).unwrap();
+        assert_eq!(status.unwrap().exit, CommandExit::Code(0));
+        assert!(lines.iter().any(|(_, line)| line.trim().contains('s')), "{:?}", lines);
+    }
+
+    #[test]
+    fn adjust_interval_never_moves_against_the_key() {
+        let mut pane = Pane::new(config(&["-c", "true", "-i", "2h"]), Rect::new(0, 0, 80, 24)).unwrap();
+        pane.adjust_interval(false);
+        assert_eq!(pane.config.interval, Duration::from_secs(7200));
+        pane.adjust_interval(true);
+        assert!(pane.config.interval < Duration::from_secs(7200));
+        
+        let mut pane = Pane::new(config(&["-c", "true", "-i", "50m"]), Rect::new(0, 0, 80, 24)).unwrap();
+        pane.adjust_interval(false);
+        assert_eq!(pane.config.interval, MAX_INTERVAL);
+        
+        let mut pane = Pane::new(config(&["-c", "true", "-i", "0.1"]), Rect::new(0, 0, 80, 24)).unwrap();
+        pane.adjust_interval(true);
+        assert_eq!(pane.config.interval, MIN_INTERVAL);
+        pane.adjust_interval(false);
+        assert!(pane.config.interval > MIN_INTERVAL);
+    }
+
+    #[test]
+    fn pane_starts_first_read_in_background() {
+        let started = Instant::now();
+        let mut pane = Pane::new(config(&["-c", "sleep 0.5; echo ready"]), Rect::new(0, 0, 80, 24)).unwrap();
+        assert!(started.elapsed() < Duration::from_millis(400));
+        assert!(pane.state.reading);
+        assert_eq!(pane.state.content, ["正在读取: sleep 0.5; echo ready"]);
+        let result = pane.results.recv().unwrap();
+        assert!(!pane.refresh(result));
+        assert_eq!(pane.state.content, ["ready"]);
+        assert_eq!(pane.state.refresh_count, 1);
+    }
+    
+    #[test]
+    fn pane_shows_missing_file_after_first_read() {
+        let path = temp_path("missing.log");
+        let mut pane = Pane::new(config(&["-f", path.to_str().unwrap()]), Rect::new(0, 0, 80, 24)).unwrap();
+        let result = pane.results.recv().unwrap();
+        pane.refresh(result);
+        assert!(pane.state.file_missing);
+        assert_eq!(pane.state.content, [format!("等待文件出现: {}", path.display())]);
+    }
+
+    #[test]
+    fn should_exit_once_times_reads_arrive() {
+        let mut pane = Pane::new(config(&["-c", "echo x", "--times", "2"]), Rect::new(0, 0, 80, 24)).unwrap();
+        let result = pane.results.recv().unwrap();
+        let changed = pane.refresh(result);
+        assert!(!pane.should_exit(changed));
+        pane.state.toggle_pause();
+        pane.start_read();
+        let result = pane.results.recv().unwrap();
+        let changed = pane.refresh(result);
+        assert!(pane.should_exit(changed));
+    }
+
+    #[test]
+    fn compute_is_evaluated_once_per_update() {
+        let path = temp_path("compute.log");
+        std::fs::write(&path, "used 3\ntotal 4\n").unwrap();
+        let mut pane = Pane::new(config(&["-f", path.to_str().unwrap(), "--compute", "/used/2 / /total/2"]), Rect::new(0, 0, 80, 24)).unwrap();
+        assert_eq!(pane.state.computed, None);
+        let result = pane.results.recv().unwrap();
+        pane.refresh(result);
+        assert_eq!(pane.state.computed, Some(Ok(0.75)));
+        pane.state.source_content.clear();
+        assert_eq!(pane.state.computed, Some(Ok(0.75)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    fn serve_once(body: &'static str) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request);
+            let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        format!("http://{}/", address)
+    }
+    
+    #[test]
+    fn read_url_marks_truncated_responses() {
+        let url = serve_once("0123456789\nabcdefghij\n");
+        let capped = config(&["--url", &url, "--max-bytes", "15"]);
+        let lines = read_url(&capped, &url).unwrap();
+        assert_eq!(lines[..2], ["0123456789", "abcd"]);
+        assert!(strip_ansi(&lines[2]).starts_with("[已截断] 响应超过 15"));
+        
+        let url = serve_once("short\n");
+        let exact = config(&["--url", &url, "--max-bytes", "6"]);
+        assert_eq!(read_url(&exact, &url).unwrap(), ["short"]);
+    }
+}